@@ -0,0 +1,322 @@
+use crate::context_switches;
+use crate::is_process_shown;
+use crate::sort_processes;
+use crate::ProcessFilter;
+use crate::SortColumn;
+use std::io;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+use taskmanager_core::format_cpu_list;
+use taskmanager_core::ProcInfo;
+use taskmanager_core::ProcRoot;
+use taskmanager_core::Sampler;
+
+pub const USAGE: &str = "Usage: taskmanager [--proc-root DIR [--passwd FILE]] [--tui | --snapshot [OPTIONS]]
+
+Without arguments the graphical task manager is started.
+
+  --tui                 run the task manager in the terminal
+  --snapshot            print the processes once and exit
+  --proc-root DIR       read the processes from DIR instead of /proc, like the /proc of the host
+                        bind-mounted into a container
+  --passwd FILE         look up the user names of --proc-root in FILE, like the /etc/passwd of
+                        the container; without it users of another root are shown as uids
+  -h, --help            print this help
+
+Snapshot options:
+  --format FORMAT       json, csv or table (default: table)
+  --all                 include processes owned by root, like \"Show all processes\"
+  --filter TEXT         keep processes whose name, user, path or PID contains TEXT
+  --regex               treat the filter text as a regular expression
+  --sort COLUMN         sort by one of the columns below (default: pid)
+  --reverse             sort in descending order
+  --columns LIST        the columns of the table and of the CSV, separated by commas, or all
+                        (default: name,user,pid,status,cpu,mem,path, and ppid for CSV)
+  --interval SECONDS    measure CPU% over this interval instead of the process lifetime
+
+Columns: name, user, pid, status, cpu, mem, path, ppid, threads, nice, priority, virtual,
+shared, swap, started, elapsed, cpu-time, command, tty, session, cgroup, read, write, fds,
+ctx-switches, io-priority and affinity. JSON always has every value.";
+
+pub struct Args {
+    pub mode: Mode,
+    pub proc_root: ProcRoot,
+}
+
+pub enum Mode {
+    Gui,
+    Tui,
+    Help,
+    Snapshot(SnapshotOptions),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    Table,
+}
+
+pub struct SnapshotOptions {
+    pub format: OutputFormat,
+    pub show_all_processes: bool,
+    pub filter_text: String,
+    pub filter_is_regex: bool,
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    // None for the default columns of the format
+    pub columns: Option<Vec<SortColumn>>,
+    pub interval: Option<Duration>,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        SnapshotOptions {
+            format: OutputFormat::Table,
+            show_all_processes: false,
+            filter_text: String::new(),
+            filter_is_regex: false,
+            sort_column: SortColumn::Pid,
+            sort_descending: false,
+            columns: None,
+            interval: None,
+        }
+    }
+}
+//################################################################
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut mode = Mode::Gui;
+    let mut proc_root = ProcRoot::default();
+    let mut passwd = None;
+    let mut options = SnapshotOptions::default();
+    let mut has_snapshot_options = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--tui" => mode = Mode::Tui,
+            "--snapshot" => mode = Mode::Snapshot(SnapshotOptions::default()),
+            "-h" | "--help" => {
+                return Ok(Args {
+                    mode: Mode::Help,
+                    proc_root,
+                })
+            }
+            "--proc-root" => proc_root = ProcRoot::new(value("--proc-root")?),
+            "--passwd" => passwd = Some(value("--passwd")?),
+            "--format" => {
+                options.format = match value("--format")?.as_str() {
+                    "json" => OutputFormat::Json,
+                    "csv" => OutputFormat::Csv,
+                    "table" => OutputFormat::Table,
+                    other => return Err(format!("unknown format: {}", other)),
+                };
+            }
+            "--all" => options.show_all_processes = true,
+            "--filter" => options.filter_text = value("--filter")?,
+            "--regex" => options.filter_is_regex = true,
+            "--sort" => {
+                let name = value("--sort")?;
+                options.sort_column =
+                    SortColumn::from_name(&name).ok_or(format!("unknown sort column: {}", name))?;
+            }
+            "--reverse" => options.sort_descending = true,
+            "--columns" => {
+                let names = value("--columns")?;
+                let columns = if names == "all" {
+                    SortColumn::ALL.to_vec()
+                } else {
+                    names
+                        .split(',')
+                        .map(|name| SortColumn::from_name(name.trim()).ok_or(format!("unknown column: {}", name)))
+                        .collect::<Result<Vec<_>, _>>()?
+                };
+                options.columns = Some(columns);
+            }
+            "--interval" => {
+                let seconds = value("--interval")?;
+                let seconds = seconds
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|seconds| seconds.is_finite() && *seconds > 0.)
+                    .ok_or(format!("invalid interval: {}", seconds))?;
+                options.interval = Some(Duration::from_secs_f32(seconds));
+            }
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+        if !["--tui", "--snapshot", "--proc-root", "--passwd"].contains(&arg.as_str()) {
+            has_snapshot_options = true;
+        }
+    }
+
+    // the root may come after --passwd
+    if let Some(passwd) = passwd {
+        proc_root = proc_root.with_passwd(passwd);
+    }
+    let mode = match mode {
+        Mode::Snapshot(_) => Mode::Snapshot(options),
+        _ if has_snapshot_options => return Err(String::from("snapshot options need --snapshot")),
+        mode => mode,
+    };
+    Ok(Args { mode, proc_root })
+}
+//################################################################
+pub fn run_snapshot(options: &SnapshotOptions, proc_root: ProcRoot) -> io::Result<()> {
+    let process_filter = ProcessFilter::new(&options.filter_text, options.filter_is_regex)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+    let mut sampler = Sampler::with_root(proc_root);
+    if let Some(interval) = options.interval {
+        // the first pass only records the cpu ticks that the second one is measured against
+        sampler.processes().map_err(io::Error::other)?;
+        thread::sleep(interval);
+    }
+    let process_map = sampler.processes().map_err(io::Error::other)?;
+
+    let mut process_vec: Vec<&ProcInfo> = process_map
+        .values()
+        .filter(|process| is_process_shown(process, options.show_all_processes, process_filter.as_ref()))
+        .collect();
+    sort_processes(&mut process_vec, options.sort_column, options.sort_descending);
+
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    let result = match options.format {
+        OutputFormat::Json => write_json(&mut output, &process_vec),
+        OutputFormat::Csv => {
+            let default_columns = || [&SortColumn::DEFAULT[..], &[SortColumn::ParentPid]].concat();
+            let columns = options.columns.clone().unwrap_or_else(default_columns);
+            write_csv(&mut output, &process_vec, &columns)
+        }
+        OutputFormat::Table => {
+            let columns = options.columns.clone().unwrap_or_else(|| SortColumn::DEFAULT.to_vec());
+            write_table(&mut output, &process_vec, &columns)
+        }
+    }
+    .and_then(|()| output.flush());
+
+    match result {
+        // piping into head and the like is not an error
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+//################################################################
+fn write_json(output: &mut impl Write, process_vec: &[&ProcInfo]) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *output, process_vec)?;
+    writeln!(output)
+}
+//################################################################
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// the header of a column in the CSV, with the unit of its plain values
+fn csv_header(column: SortColumn) -> &'static str {
+    match column {
+        SortColumn::Memory => "memory_mb",
+        SortColumn::ParentPid => "parent_pid",
+        SortColumn::VirtualMemory => "virtual_mb",
+        SortColumn::SharedMemory => "shared_mb",
+        SortColumn::Swap => "swap_mb",
+        SortColumn::StartTime => "start_seconds",
+        SortColumn::ElapsedTime => "elapsed_seconds",
+        SortColumn::CpuTime => "cpu_seconds",
+        SortColumn::CommandLine => "command_line",
+        SortColumn::IoRead => "read_bytes_per_second",
+        SortColumn::IoWrite => "write_bytes_per_second",
+        SortColumn::OpenFiles => "open_files",
+        SortColumn::ContextSwitches => "context_switches",
+        SortColumn::IoPriority => "io_priority",
+        SortColumn::CpuAffinity => "cpu_affinity",
+        column => column.name(),
+    }
+}
+
+// numbers without units, and nothing for the values that could not be read
+fn csv_value(column: SortColumn, process: &ProcInfo) -> String {
+    match column {
+        SortColumn::Cpu => format!("{:.2}", process.cpu),
+        SortColumn::Memory => format!("{:.2}", process.memory_used),
+        SortColumn::VirtualMemory => format!("{:.2}", process.virtual_memory),
+        SortColumn::SharedMemory => format!("{:.2}", process.shared_memory),
+        SortColumn::Swap => format!("{:.2}", process.swap),
+        SortColumn::StartTime => format!("{:.2}", process.start_time),
+        SortColumn::ElapsedTime => format!("{:.2}", process.elapsed_time),
+        SortColumn::CpuTime => format!("{:.2}", process.cpu_time),
+        SortColumn::IoRead => process.io_read_rate.map_or(String::new(), |rate| format!("{:.0}", rate)),
+        SortColumn::IoWrite => process.io_write_rate.map_or(String::new(), |rate| format!("{:.0}", rate)),
+        SortColumn::OpenFiles => process.open_files.map_or(String::new(), |count| count.to_string()),
+        SortColumn::ContextSwitches => context_switches(process).to_string(),
+        SortColumn::IoPriority => process.io_priority.map_or(String::new(), |priority| priority.label()),
+        SortColumn::CpuAffinity => csv_field(&format_cpu_list(&process.cpu_affinity)),
+        column => csv_field(&column.cell_text(process)),
+    }
+}
+
+fn write_csv(output: &mut impl Write, process_vec: &[&ProcInfo], columns: &[SortColumn]) -> io::Result<()> {
+    let header: Vec<&str> = columns.iter().map(|column| csv_header(*column)).collect();
+    writeln!(output, "{}", header.join(","))?;
+    for process in process_vec {
+        let values: Vec<String> = columns.iter().map(|column| csv_value(*column, process)).collect();
+        writeln!(output, "{}", values.join(","))?;
+    }
+    Ok(())
+}
+//################################################################
+// numbers are right aligned
+fn is_right_aligned(column: SortColumn) -> bool {
+    !matches!(
+        column,
+        SortColumn::Name
+            | SortColumn::User
+            | SortColumn::Status
+            | SortColumn::Path
+            | SortColumn::CommandLine
+            | SortColumn::Tty
+            | SortColumn::Cgroup
+            | SortColumn::IoPriority
+            | SortColumn::CpuAffinity
+    )
+}
+
+fn write_table(output: &mut impl Write, process_vec: &[&ProcInfo], columns: &[SortColumn]) -> io::Result<()> {
+    let rows: Vec<Vec<String>> = process_vec
+        .iter()
+        .map(|process| columns.iter().map(|column| column.cell_text(process)).collect())
+        .collect();
+    let header: Vec<String> = columns.iter().map(|column| column.title().to_string()).collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|title| title.chars().count()).collect();
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .zip(columns)
+            .enumerate()
+            .map(|(index, ((value, width), column))| {
+                if is_right_aligned(*column) {
+                    format!("{:>width$}", value, width = width)
+                } else if index + 1 == columns.len() {
+                    // the last column does not need any padding
+                    value.clone()
+                } else {
+                    format!("{:<width$}", value, width = width)
+                }
+            })
+            .collect();
+        writeln!(output, "{}", line.join("  "))?;
+    }
+    Ok(())
+}
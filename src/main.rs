@@ -1,221 +1,2140 @@
+mod cli;
+mod preferences;
+mod tui;
+
 use eframe::NativeOptions;
 use egui::Color32;
 use egui::FontId;
 use egui::RichText;
 use egui::Ui;
+use preferences::ColumnSettings;
+use preferences::Preferences;
+use preferences::MIN_COLUMN_WIDTH;
+use serde::Deserialize;
+use serde::Serialize;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
-use std::fs;
-use std::io;
-use std::process::Command;
-use std::str::FromStr;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering as AtomicOrdering;
 use std::sync::Arc;
+use std::sync::Condvar;
 use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
+use std::time::Instant;
+use taskmanager_core::describe_syscall_error;
+use taskmanager_core::ACCESS_DENIED;
+use taskmanager_core::NOT_AVAILABLE;
+use taskmanager_core::kb_to_gb;
+use taskmanager_core::find_handles;
+use taskmanager_core::format_cpu_list;
+use taskmanager_core::online_cpu_count;
+use taskmanager_core::read_connections;
+use taskmanager_core::send_signal;
+use taskmanager_core::set_cpu_affinity;
+use taskmanager_core::set_io_priority;
+use taskmanager_core::set_nice;
+use taskmanager_core::Connection;
+use taskmanager_core::CpuBreakdown;
+use taskmanager_core::CpuUsage;
+use taskmanager_core::DiskUsage;
+use taskmanager_core::Handle;
+use taskmanager_core::HandleKind;
+use taskmanager_core::InterfaceUsage;
+use taskmanager_core::IoPriority;
+use taskmanager_core::IoPriorityClass;
+use taskmanager_core::MemoryInfo;
+use taskmanager_core::ProcInfo;
+use taskmanager_core::ProcessDetails;
+use taskmanager_core::ProcessFields;
+use taskmanager_core::ProcessSignal;
+use taskmanager_core::ProcessStat;
+use taskmanager_core::ProcRoot;
+use taskmanager_core::ProcessTree;
+use taskmanager_core::SocketProtocol;
+use taskmanager_core::Sampler;
+use taskmanager_core::ThreadInfo;
 #[derive(Default)]
 struct App {
     is_list_mode: bool,
-    is_process_mode: bool,
+    tab: Tab,
     show_all_procesess: bool,
-    process_data_mutex: Arc<Mutex<BTreeMap<u32, ProcInfo>>>,
-    total_cpu_usage: Arc<Mutex<f32>>,
-    memory_info: Arc<Mutex<(f32, f32)>>,
+    show_threads: bool,
+    show_subtree_totals: bool,
+    // processes whose threads are listed below them, in addition to show_threads
+    expanded_pids: HashSet<u32>,
+    // tree nodes that the user opened or closed, see is_tree_node_open
+    toggled_tree_pids: HashSet<u32>,
+    process_data_mutex: Arc<Mutex<ProcessData>>,
+    cpu_usage: Arc<Mutex<CpuUsage>>,
+    memory_info: Arc<Mutex<MemoryInfo>>,
+    cpu_history: Arc<Mutex<History>>,
+    memory_history: Arc<Mutex<History>>,
+    disk_usage: Arc<Mutex<Vec<DiskUsage>>>,
+    disk_history: Arc<Mutex<BTreeMap<String, DiskHistory>>>,
+    // also list partitions and loop and ram devices
+    show_all_disks: bool,
+    // the device shown in the disk graph
+    selected_disk: Option<String>,
+    network_usage: Arc<Mutex<Vec<InterfaceUsage>>>,
+    network_history: Arc<Mutex<BTreeMap<String, NetworkHistory>>>,
+    // also list the loopback interface, bridges, tunnels and container interfaces
+    show_all_interfaces: bool,
+    // the interface shown in the network graph
+    selected_interface: Option<String>,
+    history_window: Duration,
+    refresh_control: Arc<RefreshControl>,
+    // the interval restored by the resume button
+    resume_interval: Duration,
+    selected_pid: Option<u32>,
+    // filled by a worker thread, see start_details_load
+    process_details: Arc<Mutex<DetailsLoad>>,
+    proc_root: ProcRoot,
+    pending_signal: Option<PendingSignal>,
+    signal_error: Option<String>,
+    priority_editor: Option<PriorityEditor>,
+    sort_column: SortColumn,
+    sort_descending: bool,
+    filter_text: String,
+    filter_is_regex: bool,
+    process_filter: Option<ProcessFilter>,
+    filter_error: Option<String>,
+    cpu_mode: CpuMode,
+    core_count: usize,
+    connections: Arc<Mutex<Vec<Connection>>>,
+    // the connections are only collected while their tab is open, see spawn_collectors
+    connections_wanted: Arc<AtomicBool>,
+    // the threads of the processes are only collected while they are shown, see threads_wanted
+    threads_wanted: Arc<AtomicBool>,
+    // the optional values of the processes that the columns need, see wanted_process_fields
+    process_fields: Arc<Mutex<ProcessFields>>,
+    connection_filter: String,
+    listening_only: bool,
+    show_handle_search: bool,
+    handle_pattern: String,
+    // filled by a worker thread, see start_handle_search
+    handle_search: Arc<Mutex<HandleSearch>>,
+    // the process list scrolls to this process the next time it is drawn, see reveal_process
+    scroll_to_pid: Option<u32>,
+    // the rows of the list or the tree view, sorted once per pass or change of the view, see
+    // update_row_cache
+    row_cache: Option<(RowOrder, Vec<CachedRow>)>,
+    // in the order in which they are shown, including the hidden ones
+    columns: Vec<ColumnSettings>,
+    dark_mode: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Tab {
+    #[default]
+    Processes,
+    Performance,
+    Connections,
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+enum CpuMode {
+    // Irix mode, like top: 100% means one full core
+    #[default]
+    PerCore,
+    // Solaris mode: 100% means every core of the machine
+    WholeMachine,
+}
+
+impl CpuMode {
+    fn label(self) -> &'static str {
+        match self {
+            CpuMode::PerCore => "Per core",
+            CpuMode::WholeMachine => "Whole machine",
+        }
+    }
+}
+
+enum ProcessFilter {
+    Substring(String),
+    Regex(regex::Regex),
+}
+
+impl ProcessFilter {
+    // an empty filter text means that every process is shown
+    fn new(text: &str, is_regex: bool) -> Result<Option<Self>, regex::Error> {
+        if text.is_empty() {
+            return Ok(None);
+        }
+        if is_regex {
+            Ok(Some(ProcessFilter::Regex(regex::Regex::new(text)?)))
+        } else {
+            Ok(Some(ProcessFilter::Substring(text.to_lowercase())))
+        }
+    }
+
+    fn matches(&self, process: &ProcInfo) -> bool {
+        let pid = process.pid.to_string();
+        let fields = [process.name.as_str(), process.user.as_str(), process.path.as_str(), pid.as_str()];
+        match self {
+            ProcessFilter::Substring(text) => fields
+                .iter()
+                .any(|field| field.to_lowercase().contains(text.as_str())),
+            ProcessFilter::Regex(regex) => fields.iter().any(|field| regex.is_match(field)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+enum SortColumn {
+    Name,
+    User,
+    #[default]
+    Pid,
+    Status,
+    Cpu,
+    Memory,
+    Path,
+    ParentPid,
+    Threads,
+    Nice,
+    Priority,
+    VirtualMemory,
+    SharedMemory,
+    Swap,
+    StartTime,
+    ElapsedTime,
+    CpuTime,
+    CommandLine,
+    Tty,
+    Session,
+    Cgroup,
+    IoRead,
+    IoWrite,
+    OpenFiles,
+    ContextSwitches,
+    IoPriority,
+    CpuAffinity,
+}
+
+fn is_process_shown(process: &ProcInfo, show_all_processes: bool, process_filter: Option<&ProcessFilter>) -> bool {
+    // without a passwd file for the procfs root, root is shown as its uid
+    if !show_all_processes && (process.user == "root" || process.user == "0") {
+        return false;
+    }
+    match process_filter {
+        Some(filter) => filter.matches(process),
+        None => true,
+    }
+}
+
+fn sort_processes(process_vec: &mut [&ProcInfo], sort_column: SortColumn, descending: bool) {
+    process_vec.sort_by(|first, second| {
+        let ordering = sort_column.compare(first, second);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+// the given processes together with all of their ancestors, so a tree view filtered
+// down to some processes still shows where they live in the hierarchy
+fn with_ancestors(process_map: &BTreeMap<u32, ProcInfo>, pids: impl Iterator<Item = u32>) -> HashSet<u32> {
+    let mut visible_pids = HashSet::new();
+    for pid in pids {
+        visible_pids.insert(pid);
+        let mut parent_pid = process_map.get(&pid).map_or(0, |process| process.parent_pid);
+        while let Some(parent) = process_map.get(&parent_pid) {
+            if !visible_pids.insert(parent.pid) {
+                break;
+            }
+            parent_pid = parent.parent_pid;
+        }
+    }
+    visible_pids
+}
+
+impl SortColumn {
+    // the columns that are shown until the user picks others, and the only ones of the
+    // terminal and the snapshot table
+    const DEFAULT: [SortColumn; 7] = [
+        SortColumn::Name,
+        SortColumn::User,
+        SortColumn::Pid,
+        SortColumn::Status,
+        SortColumn::Cpu,
+        SortColumn::Memory,
+        SortColumn::Path,
+    ];
+    const ALL: [SortColumn; 27] = [
+        SortColumn::Name,
+        SortColumn::User,
+        SortColumn::Pid,
+        SortColumn::Status,
+        SortColumn::Cpu,
+        SortColumn::Memory,
+        SortColumn::Path,
+        SortColumn::ParentPid,
+        SortColumn::Threads,
+        SortColumn::Nice,
+        SortColumn::Priority,
+        SortColumn::VirtualMemory,
+        SortColumn::SharedMemory,
+        SortColumn::Swap,
+        SortColumn::StartTime,
+        SortColumn::ElapsedTime,
+        SortColumn::CpuTime,
+        SortColumn::CommandLine,
+        SortColumn::Tty,
+        SortColumn::Session,
+        SortColumn::Cgroup,
+        SortColumn::IoRead,
+        SortColumn::IoWrite,
+        SortColumn::OpenFiles,
+        SortColumn::ContextSwitches,
+        SortColumn::IoPriority,
+        SortColumn::CpuAffinity,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        SortColumn::ALL.into_iter().find(|column| column.name().eq_ignore_ascii_case(name))
+    }
+
+    // the name of the column on the command line
+    fn name(self) -> &'static str {
+        match self {
+            SortColumn::Name => "name",
+            SortColumn::User => "user",
+            SortColumn::Pid => "pid",
+            SortColumn::Status => "status",
+            SortColumn::Cpu => "cpu",
+            SortColumn::Memory => "mem",
+            SortColumn::Path => "path",
+            SortColumn::ParentPid => "ppid",
+            SortColumn::Threads => "threads",
+            SortColumn::Nice => "nice",
+            SortColumn::Priority => "priority",
+            SortColumn::VirtualMemory => "virtual",
+            SortColumn::SharedMemory => "shared",
+            SortColumn::Swap => "swap",
+            SortColumn::StartTime => "started",
+            SortColumn::ElapsedTime => "elapsed",
+            SortColumn::CpuTime => "cpu-time",
+            SortColumn::CommandLine => "command",
+            SortColumn::Tty => "tty",
+            SortColumn::Session => "session",
+            SortColumn::Cgroup => "cgroup",
+            SortColumn::IoRead => "read",
+            SortColumn::IoWrite => "write",
+            SortColumn::OpenFiles => "fds",
+            SortColumn::ContextSwitches => "ctx-switches",
+            SortColumn::IoPriority => "io-priority",
+            SortColumn::CpuAffinity => "affinity",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Name => "Name",
+            SortColumn::User => "User",
+            SortColumn::Pid => "PID",
+            SortColumn::Status => "Status",
+            SortColumn::Cpu => "CPU%",
+            SortColumn::Memory => "Mem",
+            SortColumn::Path => "Path",
+            SortColumn::ParentPid => "PPID",
+            SortColumn::Threads => "Threads",
+            SortColumn::Nice => "Nice",
+            SortColumn::Priority => "Priority",
+            SortColumn::VirtualMemory => "Virtual",
+            SortColumn::SharedMemory => "Shared",
+            SortColumn::Swap => "Swap",
+            SortColumn::StartTime => "Started",
+            SortColumn::ElapsedTime => "Elapsed",
+            SortColumn::CpuTime => "CPU time",
+            SortColumn::CommandLine => "Command line",
+            SortColumn::Tty => "TTY",
+            SortColumn::Session => "Session",
+            SortColumn::Cgroup => "Cgroup",
+            SortColumn::IoRead => "Read/s",
+            SortColumn::IoWrite => "Write/s",
+            SortColumn::OpenFiles => "FDs",
+            SortColumn::ContextSwitches => "Ctx switches",
+            SortColumn::IoPriority => "I/O priority",
+            SortColumn::CpuAffinity => "Affinity",
+        }
+    }
+
+    fn default_width(self) -> f32 {
+        match self {
+            SortColumn::Name => 200.,
+            SortColumn::User => 100.,
+            SortColumn::Pid => 70.,
+            SortColumn::Status => 60.,
+            SortColumn::Cpu => 90.,
+            SortColumn::Memory => 100.,
+            SortColumn::Path => 300.,
+            SortColumn::ParentPid | SortColumn::Threads | SortColumn::Nice | SortColumn::Priority => 70.,
+            SortColumn::Tty | SortColumn::Session | SortColumn::OpenFiles => 70.,
+            SortColumn::VirtualMemory | SortColumn::SharedMemory | SortColumn::Swap => 100.,
+            SortColumn::StartTime | SortColumn::ElapsedTime | SortColumn::CpuTime => 100.,
+            SortColumn::IoRead | SortColumn::IoWrite | SortColumn::ContextSwitches => 100.,
+            SortColumn::IoPriority | SortColumn::CpuAffinity => 110.,
+            SortColumn::CommandLine => 400.,
+            SortColumn::Cgroup => 250.,
+        }
+    }
+
+    fn compare(self, first: &ProcInfo, second: &ProcInfo) -> Ordering {
+        let ordering = match self {
+            // without a lowercase copy of every name in every comparison
+            SortColumn::Name => {
+                first.name.chars().flat_map(char::to_lowercase).cmp(second.name.chars().flat_map(char::to_lowercase))
+            }
+            SortColumn::User => first.user.cmp(&second.user),
+            SortColumn::Pid => first.pid.cmp(&second.pid),
+            SortColumn::Status => first.status.cmp(&second.status),
+            SortColumn::Cpu => first.cpu.total_cmp(&second.cpu),
+            SortColumn::Memory => first.memory_used.total_cmp(&second.memory_used),
+            SortColumn::Path => first.path.cmp(&second.path),
+            SortColumn::ParentPid => first.parent_pid.cmp(&second.parent_pid),
+            SortColumn::Threads => first.threads.cmp(&second.threads),
+            SortColumn::Nice => first.nice.cmp(&second.nice),
+            SortColumn::Priority => first.priority.cmp(&second.priority),
+            SortColumn::VirtualMemory => first.virtual_memory.total_cmp(&second.virtual_memory),
+            SortColumn::SharedMemory => first.shared_memory.total_cmp(&second.shared_memory),
+            SortColumn::Swap => first.swap.total_cmp(&second.swap),
+            SortColumn::StartTime => first.start_time.total_cmp(&second.start_time),
+            SortColumn::ElapsedTime => first.elapsed_time.total_cmp(&second.elapsed_time),
+            SortColumn::CpuTime => first.cpu_time.total_cmp(&second.cpu_time),
+            SortColumn::CommandLine => first.command_line.cmp(&second.command_line),
+            SortColumn::Tty => first.tty.cmp(&second.tty),
+            SortColumn::Session => first.session.cmp(&second.session),
+            SortColumn::Cgroup => first.cgroup.cmp(&second.cgroup),
+            // the unreadable ones come first
+            SortColumn::IoRead => first.io_read_rate.unwrap_or(-1.).total_cmp(&second.io_read_rate.unwrap_or(-1.)),
+            SortColumn::IoWrite => first.io_write_rate.unwrap_or(-1.).total_cmp(&second.io_write_rate.unwrap_or(-1.)),
+            SortColumn::OpenFiles => first.open_files.cmp(&second.open_files),
+            SortColumn::ContextSwitches => context_switches(first).cmp(&context_switches(second)),
+            SortColumn::IoPriority => first.io_priority.map(IoPriority::as_raw).cmp(&second.io_priority.map(IoPriority::as_raw)),
+            // the processes that may run on fewer cpus come first
+            SortColumn::CpuAffinity => (first.cpu_affinity.len(), &first.cpu_affinity).cmp(&(second.cpu_affinity.len(), &second.cpu_affinity)),
+        };
+        // keep rows with equal keys in a stable pid order
+        ordering.then(first.pid.cmp(&second.pid))
+    }
+
+    // the value of a process in this column, as the list and the snapshot table show it
+    fn cell_text(self, process: &ProcInfo) -> String {
+        match self {
+            SortColumn::Name => process.name.to_string(),
+            SortColumn::User => process.user.to_string(),
+            SortColumn::Pid => process.pid.to_string(),
+            SortColumn::Status => process.status.to_string(),
+            SortColumn::Cpu => format!("{:.2}%", process.cpu),
+            SortColumn::Memory => format!("{:.2} Mb", process.memory_used),
+            SortColumn::Path => process.path.to_string(),
+            SortColumn::ParentPid => process.parent_pid.to_string(),
+            SortColumn::Threads => process.threads.to_string(),
+            SortColumn::Nice => process.nice.to_string(),
+            SortColumn::Priority => process.priority.to_string(),
+            SortColumn::VirtualMemory => format!("{:.2} Mb", process.virtual_memory),
+            SortColumn::SharedMemory => format!("{:.2} Mb", process.shared_memory),
+            SortColumn::Swap => format!("{:.2} Mb", process.swap),
+            SortColumn::StartTime => format_duration(process.start_time),
+            SortColumn::ElapsedTime => format_duration(process.elapsed_time),
+            SortColumn::CpuTime => format_duration(process.cpu_time),
+            SortColumn::CommandLine => process.command_line.to_string(),
+            SortColumn::Tty => process.tty.to_string(),
+            SortColumn::Session => process.session.to_string(),
+            SortColumn::Cgroup => process.cgroup.to_string(),
+            SortColumn::IoRead => process.io_read_rate.map_or(String::from(NOT_AVAILABLE), format_rate),
+            SortColumn::IoWrite => process.io_write_rate.map_or(String::from(NOT_AVAILABLE), format_rate),
+            SortColumn::OpenFiles => process.open_files.map_or(String::from(NOT_AVAILABLE), |count| count.to_string()),
+            SortColumn::ContextSwitches => context_switches(process).to_string(),
+            SortColumn::IoPriority => process.io_priority.map_or(String::from(NOT_AVAILABLE), IoPriority::label),
+            SortColumn::CpuAffinity if process.cpu_affinity.is_empty() => String::from(NOT_AVAILABLE),
+            SortColumn::CpuAffinity => format_cpu_list(&process.cpu_affinity),
+        }
+    }
+}
+
+fn context_switches(process: &ProcInfo) -> u64 {
+    process.voluntary_context_switches + process.involuntary_context_switches
+}
+
+// One row of the list view. The threads of a process are listed right below it.
+enum ListRow<'a> {
+    Process(&'a ProcInfo),
+    Thread(&'a ProcInfo, &'a ThreadInfo),
+}
+
+// One row of the tree view, with the row of its parent somewhere above it.
+struct TreeRow<'a> {
+    row: ListRow<'a>,
+    depth: usize,
+    // the row has children or threads to show
+    is_expandable: bool,
+    totals: Option<SubtreeTotals>,
+}
+
+// Everything the rows of the process list depend on; the rows are sorted again when it changes.
+#[derive(Clone, PartialEq)]
+struct RowOrder {
+    // counts the passes of the process collector
+    pass: u64,
+    is_list_mode: bool,
+    sort_column: SortColumn,
+    sort_descending: bool,
+    show_all_processes: bool,
+    show_threads: bool,
+    show_subtree_totals: bool,
+    filter_text: String,
+    filter_is_regex: bool,
+    expanded_pids: HashSet<u32>,
+    toggled_tree_pids: HashSet<u32>,
+}
+
+// A row of the list or the tree view by pid, so it can be kept between frames without holding
+// the lock of the process data.
+#[derive(Clone, Copy)]
+struct CachedRow {
+    pid: u32,
+    tid: Option<u32>,
+    depth: usize,
+    is_expandable: bool,
+    totals: Option<SubtreeTotals>,
+}
+
+impl CachedRow {
+    fn new(row: &ListRow, depth: usize, is_expandable: bool, totals: Option<SubtreeTotals>) -> Self {
+        let (pid, tid) = match row {
+            ListRow::Process(process) => (process.pid, None),
+            ListRow::Thread(process, thread) => (process.pid, Some(thread.tid)),
+        };
+        CachedRow {
+            pid,
+            tid,
+            depth,
+            is_expandable,
+            totals,
+        }
+    }
+
+    fn resolve<'a>(&self, process_map: &'a BTreeMap<u32, ProcInfo>) -> Option<ListRow<'a>> {
+        let process = process_map.get(&self.pid)?;
+        match self.tid {
+            None => Some(ListRow::Process(process)),
+            Some(tid) => {
+                let thread = process.thread_list.iter().find(|thread| thread.tid == tid)?;
+                Some(ListRow::Thread(process, thread))
+            }
+        }
+    }
+}
+
+// The usage of a process together with all of its descendants.
+#[derive(Clone, Copy, Default)]
+struct SubtreeTotals {
+    cpu: f32,
+    memory_used: f32,
+}
+
+fn subtree_totals(process_data: &ProcessData) -> HashMap<u32, SubtreeTotals> {
+    let tree = &process_data.tree;
+    // parents come before their children, so going backwards adds up the children first
+    let mut order = Vec::with_capacity(process_data.process_map.len());
+    let mut stack = tree.roots().to_vec();
+    while let Some(pid) = stack.pop() {
+        order.push(pid);
+        stack.extend_from_slice(tree.children(pid));
+    }
+
+    let mut totals: HashMap<u32, SubtreeTotals> = HashMap::with_capacity(order.len());
+    for pid in order.into_iter().rev() {
+        let Some(process) = process_data.process_map.get(&pid) else {
+            continue;
+        };
+        let mut total = SubtreeTotals {
+            cpu: process.cpu,
+            memory_used: process.memory_used,
+        };
+        for child in tree.children(pid) {
+            if let Some(child_total) = totals.get(child) {
+                total.cpu += child_total.cpu;
+                total.memory_used += child_total.memory_used;
+            }
+        }
+        totals.insert(pid, total);
+    }
+    totals
+}
+
+// why signals and priorities can not be changed with --proc-root
+const NOT_LOCAL_ROOT: &str = "Only for the processes of the local /proc";
+
+struct PendingSignal {
+    pid: u32,
+    name: String,
+    signal: ProcessSignal,
+}
+
+// The state of the Find handle window.
+#[derive(Default)]
+enum HandleSearch {
+    #[default]
+    Idle,
+    Searching,
+    Done(taskmanager_core::Result<Vec<Handle>>),
+}
+
+// The state of the details panel.
+#[derive(Default)]
+enum DetailsLoad {
+    #[default]
+    Idle,
+    Loading(u32),
+    Done(Box<ProcessDetails>),
+}
+
+// The values in the priority window, see show_priority_editor.
+struct PriorityEditor {
+    pid: u32,
+    name: String,
+    nice: i32,
+    io_priority: IoPriority,
+    // one checkbox per cpu
+    cpus: Vec<bool>,
+    // the values the process had, only the ones the user changed are set
+    applied_nice: i32,
+    applied_io_priority: IoPriority,
+    applied_cpus: Vec<bool>,
+    errors: Vec<String>,
 }
 
 impl App {
     fn new(
         cc: &eframe::CreationContext<'_>,
-        process_data_mutex: Arc<Mutex<BTreeMap<u32, ProcInfo>>>,
-        total_cpu_usage: Arc<Mutex<f32>>,
-        memory_info: Arc<Mutex<(f32, f32)>>,
+        collectors: Collectors,
     ) -> Self {
-        // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
-        // Restore app state using cc.storage (requires the "persistence" feature).
-        // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
-        // for e.g. egui::PaintCallback.
         if let Some(cpu_usage) = cc.integration_info.cpu_usage {
             println!("Cpu usage: {}", cpu_usage);
         }
+        // the preferences of the last run, the window size is restored by eframe
+        let mut preferences: Preferences = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        preferences.normalize();
+        cc.egui_ctx.set_visuals(if preferences.dark_mode {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        });
+        collectors.refresh_control.set_rate(preferences.refresh_rate);
+
         Self {
-            is_list_mode: true,
-            is_process_mode: true,
-            show_all_procesess: false,
-            process_data_mutex,
-            total_cpu_usage,
-            memory_info,
+            is_list_mode: preferences.is_list_mode,
+            tab: Tab::Processes,
+            show_all_procesess: preferences.show_all_processes,
+            show_threads: preferences.show_threads,
+            show_subtree_totals: preferences.show_subtree_totals,
+            expanded_pids: HashSet::new(),
+            toggled_tree_pids: HashSet::new(),
+            process_data_mutex: collectors.process_data,
+            cpu_usage: collectors.cpu_usage,
+            memory_info: collectors.memory_info,
+            cpu_history: collectors.cpu_history,
+            memory_history: collectors.memory_history,
+            disk_usage: collectors.disk_usage,
+            disk_history: collectors.disk_history,
+            show_all_disks: false,
+            selected_disk: None,
+            network_usage: collectors.network_usage,
+            network_history: collectors.network_history,
+            show_all_interfaces: false,
+            selected_interface: None,
+            history_window: preferences.history_window,
+            resume_interval: match preferences.refresh_rate {
+                RefreshRate::Every(interval) => interval,
+                RefreshRate::Paused => DEFAULT_REFRESH_INTERVAL,
+            },
+            refresh_control: collectors.refresh_control,
+            selected_pid: None,
+            process_details: Arc::new(Mutex::new(DetailsLoad::default())),
+            proc_root: collectors.proc_root,
+            pending_signal: None,
+            priority_editor: None,
+            signal_error: None,
+            sort_column: preferences.sort_column,
+            sort_descending: preferences.sort_descending,
+            filter_text: String::new(),
+            filter_is_regex: false,
+            process_filter: None,
+            filter_error: None,
+            cpu_mode: preferences.cpu_mode,
+            core_count: online_cpu_count(),
+            connections: collectors.connections,
+            connections_wanted: collectors.connections_wanted,
+            threads_wanted: collectors.threads_wanted,
+            process_fields: collectors.process_fields,
+            connection_filter: String::new(),
+            listening_only: false,
+            show_handle_search: false,
+            handle_pattern: String::new(),
+            handle_search: Arc::new(Mutex::new(HandleSearch::default())),
+            scroll_to_pid: None,
+            row_cache: None,
+            columns: preferences.columns,
+            dark_mode: preferences.dark_mode,
+        }
+    }
+    fn preferences(&self) -> Preferences {
+        Preferences {
+            is_list_mode: self.is_list_mode,
+            show_all_processes: self.show_all_procesess,
+            show_threads: self.show_threads,
+            show_subtree_totals: self.show_subtree_totals,
+            columns: self.columns.clone(),
+            sort_column: self.sort_column,
+            sort_descending: self.sort_descending,
+            // a paused task manager would start without any data, so the interval is saved instead
+            refresh_rate: RefreshRate::Every(self.resume_interval),
+            cpu_mode: self.cpu_mode,
+            history_window: self.history_window,
+            dark_mode: self.dark_mode,
+        }
+    }
+    fn visible_columns(&self) -> Vec<ColumnSettings> {
+        self.columns.iter().filter(|settings| settings.visible).copied().collect()
+    }
+    // the optional values that a visible column or the sort order needs, the others are not read
+    fn wanted_process_fields(&self) -> ProcessFields {
+        let mut columns: Vec<SortColumn> = self.visible_columns().iter().map(|settings| settings.column).collect();
+        columns.push(self.sort_column);
+        let needs = |wanted: &[SortColumn]| columns.iter().any(|column| wanted.contains(column));
+        ProcessFields {
+            command_line: needs(&[SortColumn::CommandLine]),
+            cgroup: needs(&[SortColumn::Cgroup]),
+            io_rates: needs(&[SortColumn::IoRead, SortColumn::IoWrite]),
+            open_files: needs(&[SortColumn::OpenFiles]),
+            io_priority: needs(&[SortColumn::IoPriority]),
         }
     }
 
-    fn create_header_row(&self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            ui.columns(7, |columns| {
-                columns[0].label(
-                    RichText::new("Name").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
-                columns[1].label(
-                    RichText::new("User").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
-                columns[2].label(
-                    RichText::new("PID").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
-                columns[3].label(
-                    RichText::new("Status").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
-                columns[4].label(
-                    RichText::new("CPU%").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
-                columns[5].label(
-                    RichText::new("Mem").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
-                columns[6].label(
-                    RichText::new("Path").font(FontId::new(20., egui::FontFamily::Proportional)),
-                );
+    fn create_header_row(&mut self, ui: &mut Ui) {
+        let header_font = FontId::new(20., egui::FontFamily::Proportional);
+        let header_height = ui.fonts(|fonts| fonts.row_height(&header_font));
+        let header_response = ui.horizontal(|ui| {
+            for settings in self.columns.iter_mut().filter(|settings| settings.visible) {
+                let column = settings.column;
+                let mut title = String::from(column.title());
+                if self.sort_column == column {
+                    title.push_str(if self.sort_descending { " ▼" } else { " ▲" });
+                }
+                let header = egui::Label::new(RichText::new(title).font(header_font.clone())).sense(egui::Sense::click());
+                if show_cell(ui, settings.width, header_height, |ui| ui.add(header)).clicked() {
+                    if self.sort_column == column {
+                        self.sort_descending = !self.sort_descending;
+                    } else {
+                        self.sort_column = column;
+                        self.sort_descending = false;
+                    }
+                }
+
+                let (handle_rect, handle) = column_gap(ui, header_height, egui::Sense::drag());
+                if handle.dragged() {
+                    settings.width = (settings.width + handle.drag_delta().x).max(MIN_COLUMN_WIDTH);
+                }
+                let handle = handle.on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+                let stroke = ui.style().interact(&handle).fg_stroke;
+                ui.painter().vline(handle_rect.center().x, handle_rect.y_range(), stroke);
+            }
+        });
+        header_response
+            .response
+            .interact(egui::Sense::click())
+            .context_menu(|ui| self.show_column_menu(ui));
+    }
+    fn show_column_menu(&mut self, ui: &mut Ui) {
+        let mut moved = None;
+        for index in 0..self.columns.len() {
+            ui.horizontal(|ui| {
+                let settings = &mut self.columns[index];
+                // the name column can not be hidden, see Preferences::normalize
+                let can_hide = settings.column != SortColumn::Name;
+                ui.add_enabled(can_hide, egui::Checkbox::new(&mut settings.visible, settings.column.title()));
+                if ui.add_enabled(index > 0, egui::Button::new("⏶").small()).clicked() {
+                    moved = Some((index, index - 1));
+                }
+                if ui.add_enabled(index + 1 < self.columns.len(), egui::Button::new("⏷").small()).clicked() {
+                    moved = Some((index, index + 1));
+                }
             });
+        }
+        if let Some((from, to)) = moved {
+            self.columns.swap(from, to);
+        }
+        ui.separator();
+        if ui.button("Reset columns").clicked() {
+            self.columns = preferences::default_columns();
+            ui.close_menu();
+        }
+    }
+    fn visible_processes<'a>(&self, process_map: &'a BTreeMap<u32, ProcInfo>) -> Vec<&'a ProcInfo> {
+        let mut process_vec: Vec<_> = process_map
+            .values()
+            .filter(|process| self.is_process_shown(process))
+            .collect();
+        sort_processes(&mut process_vec, self.sort_column, self.sort_descending);
+        process_vec
+    }
+    fn list_rows<'a>(&self, process_map: &'a BTreeMap<u32, ProcInfo>) -> Vec<ListRow<'a>> {
+        let mut rows = Vec::new();
+        for process in self.visible_processes(process_map) {
+            rows.push(ListRow::Process(process));
+            if self.are_threads_shown(process) {
+                rows.extend(process.thread_list.iter().map(|thread| ListRow::Thread(process, thread)));
+            }
+        }
+        rows
+    }
+    // the only thread of a process is the process itself, so it is not listed
+    fn are_threads_shown(&self, process: &ProcInfo) -> bool {
+        process.thread_list.len() > 1 && (self.show_threads || self.expanded_pids.contains(&process.pid))
+    }
+    fn displayed_cpu(&self, cpu: f32) -> f32 {
+        match self.cpu_mode {
+            CpuMode::PerCore => cpu,
+            CpuMode::WholeMachine => cpu / self.core_count.max(1) as f32,
+        }
+    }
+    fn is_process_shown(&self, process: &ProcInfo) -> bool {
+        is_process_shown(process, self.show_all_procesess, self.process_filter.as_ref())
+    }
+    fn tree_visible_pids(&self, process_map: &BTreeMap<u32, ProcInfo>) -> Option<HashSet<u32>> {
+        self.process_filter.as_ref()?;
+
+        let matching_pids = process_map
+            .values()
+            .filter(|process| self.is_process_shown(process))
+            .map(|process| process.pid);
+        Some(with_ancestors(process_map, matching_pids))
+    }
+    // Shows one row of the list or the tree view in the columns of create_header_row. Returns the
+    // response of the whole row and whether the arrow in front of the name was clicked.
+    fn show_process_row(
+        &mut self,
+        ui: &mut Ui,
+        row: &ListRow,
+        indent: f32,
+        expander: Option<bool>,
+        totals: Option<SubtreeTotals>,
+    ) -> (egui::Response, bool) {
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let mut expander_clicked = false;
+        let row_response = ui.horizontal(|ui| {
+            for settings in self.visible_columns() {
+                show_cell(ui, settings.width, row_height, |ui| match (row, settings.column) {
+                    (ListRow::Process(process), SortColumn::Name) => {
+                        ui.add_space(indent);
+                        if let Some(is_open) = expander {
+                            let arrow = egui::Label::new(if is_open { "⏷" } else { "⏵" }).sense(egui::Sense::click());
+                            expander_clicked = ui.add_sized([row_height, row_height], arrow).clicked();
+                        }
+                        if ui.selectable_label(self.selected_pid == Some(process.pid), RichText::new(process.name.to_string())).clicked() {
+                            self.selected_pid = Some(process.pid);
+                        }
+                    }
+                    (ListRow::Thread(_, thread), SortColumn::Name) => {
+                        ui.add_space(indent + row_height);
+                        ui.label(RichText::new(format!("↳ {}", thread.name)).weak());
+                    }
+                    (ListRow::Process(process), column) => {
+                        ui.label(self.process_cell_text(process, column, totals));
+                    }
+                    (ListRow::Thread(process, thread), column) => {
+                        ui.label(RichText::new(self.thread_cell_text(process, thread, column)).weak());
+                    }
+                });
+                column_gap(ui, row_height, egui::Sense::hover());
+            }
         });
+        (row_response.response, expander_clicked)
     }
-    fn show_rows_as_list(&self, ui: &mut Ui) {
+    fn process_cell_text(&self, process: &ProcInfo, column: SortColumn, totals: Option<SubtreeTotals>) -> String {
+        let cpu = format!("{:.2}%", self.displayed_cpu(process.cpu));
+        match (column, totals) {
+            (SortColumn::Cpu, Some(totals)) => format!("{} (Σ {:.2}%)", cpu, self.displayed_cpu(totals.cpu)),
+            (SortColumn::Cpu, None) => cpu,
+            (SortColumn::Memory, Some(totals)) => {
+                format!("{} (Σ {:.2} Mb)", column.cell_text(process), totals.memory_used)
+            }
+            _ => column.cell_text(process),
+        }
+    }
+    // threads share the memory and the executable of their process
+    fn thread_cell_text(&self, process: &ProcInfo, thread: &ThreadInfo, column: SortColumn) -> String {
+        match column {
+            SortColumn::Name => thread.name.to_string(),
+            SortColumn::User => process.user.to_string(),
+            SortColumn::Pid => thread.tid.to_string(),
+            SortColumn::Status => thread.state.to_string(),
+            SortColumn::Cpu => format!("{:.2}%", self.displayed_cpu(thread.cpu)),
+            SortColumn::Memory => String::new(),
+            SortColumn::Path => format!("last ran on CPU {}", thread.processor),
+            SortColumn::ParentPid => process.pid.to_string(),
+            // the other values are only read for whole processes
+            _ => String::new(),
+        }
+    }
+    fn show_rows_as_list(&mut self, ui: &mut Ui) {
         let text_style = egui::TextStyle::Body;
         let row_height = ui.text_style_height(&text_style);
-        let mut total_rows: usize = 0;
-        match self.process_data_mutex.lock() {
-            Ok(process_map) => {
-                if !self.show_all_procesess
-                {
-                    let x: Vec<_>= process_map.values().filter(|process| process.user != "root").collect();
-                    total_rows = x.len();
+        let process_data_mutex = self.process_data_mutex.clone();
+        let process_data = match process_data_mutex.lock() {
+            Ok(process_data) => process_data,
+            Err(error) => {
+                println!("Error at getting process_data: {error}, so we exit function!");
+                return;
+            }
+        };
+        let (total_rows, scroll_index) = self.update_row_cache(&process_data);
+
+        self.scrolled_process_list(ui, row_height, scroll_index).show_rows(
+            ui,
+            row_height,
+            total_rows,
+            |ui: &mut Ui, total_rows: std::ops::Range<usize>| {
+                for index in total_rows {
+                    let Some(row) = self.cached_row(index).and_then(|row| row.resolve(&process_data.process_map)) else {
+                        continue;
+                    };
+                    let (ListRow::Process(process) | ListRow::Thread(process, _)) = row;
+                    // with show_threads every process is expanded already
+                    // the count is always known, the threads are only collected once they are shown
+                    let has_threads = process.threads > 1 && !self.show_threads;
+                    let (expander, indent) = match row {
+                        ListRow::Process(_) if has_threads => (Some(self.expanded_pids.contains(&process.pid)), 0.),
+                        _ => (None, row_height),
+                    };
+                    let (response, expander_clicked) = self.show_process_row(ui, &row, indent, expander, None);
+                    if expander_clicked && !self.expanded_pids.remove(&process.pid) {
+                        self.expanded_pids.insert(process.pid);
+                    }
+                    self.handle_row_response(response, process);
                 }
-                else {
-                    total_rows = process_map.len();
+            },
+        );
+    }
+    // Sorts the rows of the list or the tree view again when the collector made a pass or the
+    // view changed since the last frame. Returns the number of rows and the index of the row of
+    // scroll_to_pid.
+    fn update_row_cache(&mut self, process_data: &ProcessData) -> (usize, Option<usize>) {
+        let order = RowOrder {
+            pass: process_data.pass,
+            is_list_mode: self.is_list_mode,
+            sort_column: self.sort_column,
+            sort_descending: self.sort_descending,
+            show_all_processes: self.show_all_procesess,
+            show_threads: self.show_threads,
+            show_subtree_totals: self.show_subtree_totals,
+            filter_text: self.filter_text.clone(),
+            filter_is_regex: self.filter_is_regex,
+            expanded_pids: self.expanded_pids.clone(),
+            toggled_tree_pids: self.toggled_tree_pids.clone(),
+        };
+        if self.row_cache.as_ref().map(|(cached_order, _)| cached_order) != Some(&order) {
+            let rows = if self.is_list_mode {
+                self.list_rows(&process_data.process_map)
+                    .iter()
+                    .map(|row| CachedRow::new(row, 0, false, None))
+                    .collect()
+            } else {
+                self.tree_rows(process_data)
+                    .iter()
+                    .map(|tree_row| CachedRow::new(&tree_row.row, tree_row.depth, tree_row.is_expandable, tree_row.totals))
+                    .collect()
+            };
+            self.row_cache = Some((order, rows));
+        }
+        let rows = self.row_cache.as_ref().map_or(&[][..], |(_, rows)| &rows[..]);
+        let scroll_index = self
+            .scroll_to_pid
+            .and_then(|pid| rows.iter().position(|row| row.pid == pid && row.tid.is_none()));
+        (rows.len(), scroll_index)
+    }
+    fn cached_row(&self, index: usize) -> Option<CachedRow> {
+        self.row_cache.as_ref().and_then(|(_, rows)| rows.get(index)).copied()
+    }
+    // The scroll area of the list and the tree view, moved to the row of reveal_process once.
+    fn scrolled_process_list(&mut self, ui: &Ui, row_height: f32, scroll_index: Option<usize>) -> egui::ScrollArea {
+        let scroll_area = egui::ScrollArea::vertical().auto_shrink(false);
+        // a process that exited in the meantime is not looked for again
+        if self.scroll_to_pid.take().is_none() {
+            return scroll_area;
+        }
+        match scroll_index {
+            Some(index) => {
+                // the row ends up in the middle of the list
+                let row_top = index as f32 * (row_height + ui.spacing().item_spacing.y);
+                scroll_area.vertical_scroll_offset((row_top - ui.available_height() / 2.).max(0.))
+            }
+            None => scroll_area,
+        }
+    }
+    // Selects a process in the Processes tab, makes it visible even when the filter or the tree
+    // would hide it, and scrolls to it.
+    fn reveal_process(&mut self, pid: u32) {
+        self.tab = Tab::Processes;
+        self.selected_pid = Some(pid);
+        self.scroll_to_pid = Some(pid);
+
+        let process_data_mutex = self.process_data_mutex.clone();
+        let Ok(process_data) = process_data_mutex.lock() else {
+            return;
+        };
+        let Some(process) = process_data.process_map.get(&pid) else {
+            return;
+        };
+        if !is_process_shown(process, self.show_all_procesess, None) {
+            self.show_all_procesess = true;
+        }
+        if self.process_filter.as_ref().is_some_and(|process_filter| !process_filter.matches(process)) {
+            self.filter_text.clear();
+            self.update_process_filter();
+        }
+        for ancestor in with_ancestors(&process_data.process_map, [pid].into_iter()) {
+            if ancestor != pid && !self.is_tree_node_open(ancestor) && !self.toggled_tree_pids.remove(&ancestor) {
+                self.toggled_tree_pids.insert(ancestor);
+            }
+        }
+    }
+    fn tree_rows<'a>(&self, process_data: &'a ProcessData) -> Vec<TreeRow<'a>> {
+        let process_map = &process_data.process_map;
+        let tree = &process_data.tree;
+        let shown_pids = match self.tree_visible_pids(process_map) {
+            Some(visible_pids) => visible_pids,
+            None => process_map
+                .values()
+                .filter(|process| self.is_process_shown(process))
+                .map(|process| process.pid)
+                .collect(),
+        };
+        // the shown processes and everything above them, the other subtrees are skipped
+        let reachable_pids = with_ancestors(process_map, shown_pids.iter().copied());
+        let subtree_totals = self.show_subtree_totals.then(|| subtree_totals(process_data));
+
+        let sort_siblings = |siblings: &mut Vec<&ProcInfo>| {
+            siblings.sort_by(|first, second| {
+                let total = |pid: u32| subtree_totals.as_ref().and_then(|totals| totals.get(&pid)).copied().unwrap_or_default();
+                // with the totals shown, the siblings are ordered by them
+                let ordering = match (&subtree_totals, self.sort_column) {
+                    (Some(_), SortColumn::Cpu) => total(first.pid).cpu.total_cmp(&total(second.pid).cpu),
+                    (Some(_), SortColumn::Memory) => total(first.pid).memory_used.total_cmp(&total(second.pid).memory_used),
+                    _ => self.sort_column.compare(first, second),
+                }
+                .then(first.pid.cmp(&second.pid));
+                if self.sort_descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        };
+        // the shown processes right below `pids`, the children of a hidden process take its place
+        let shown_below = |pids: &[u32]| -> Vec<&'a ProcInfo> {
+            let mut shown = Vec::new();
+            let mut pending = pids.to_vec();
+            while let Some(pid) = pending.pop() {
+                let Some(process) = process_map.get(&pid).filter(|_| reachable_pids.contains(&pid)) else {
+                    continue;
+                };
+                if shown_pids.contains(&pid) {
+                    shown.push(process);
+                } else {
+                    pending.extend_from_slice(tree.children(pid));
                 }
             }
+            sort_siblings(&mut shown);
+            shown
+        };
+
+        let mut rows = Vec::new();
+        let mut stack: Vec<(&ProcInfo, usize)> = shown_below(tree.roots()).into_iter().rev().map(|process| (process, 0)).collect();
+        while let Some((process, depth)) = stack.pop() {
+            let children = shown_below(tree.children(process.pid));
+            let threads = if self.show_threads && process.thread_list.len() > 1 {
+                &process.thread_list[..]
+            } else {
+                &[]
+            };
+            rows.push(TreeRow {
+                row: ListRow::Process(process),
+                depth,
+                is_expandable: !children.is_empty() || !threads.is_empty(),
+                totals: subtree_totals.as_ref().and_then(|totals| totals.get(&process.pid)).copied(),
+            });
+            if self.is_tree_node_open(process.pid) {
+                rows.extend(threads.iter().map(|thread| TreeRow {
+                    row: ListRow::Thread(process, thread),
+                    depth: depth + 1,
+                    is_expandable: false,
+                    totals: None,
+                }));
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+        }
+        rows
+    }
+    // the nodes are closed by default, except while filtering, so the matches are visible
+    fn is_tree_node_open(&self, pid: u32) -> bool {
+        self.process_filter.is_some() != self.toggled_tree_pids.contains(&pid)
+    }
+    fn set_all_tree_nodes_open(&mut self, open: bool) {
+        self.toggled_tree_pids.clear();
+        // every node is toggled away from its default
+        if open != self.process_filter.is_some() {
+            if let Ok(process_data) = self.process_data_mutex.lock() {
+                self.toggled_tree_pids.extend(process_data.process_map.keys());
+            }
+        }
+    }
+    fn show_rows_as_tree(&mut self, ui: &mut Ui) {
+        let text_style = egui::TextStyle::Body;
+        let row_height = ui.text_style_height(&text_style);
+        let process_data_mutex = self.process_data_mutex.clone();
+        let process_data = match process_data_mutex.lock() {
+            Ok(process_data) => process_data,
             Err(error) => {
-                println!("Error at getting process_data length: {error}. The total_rows will be {total_rows}, so we exit function!");
+                println!("Error at getting the tree rows: {error}");
+                return;
+            }
+        };
+        let (total_rows, scroll_index) = self.update_row_cache(&process_data);
+
+        self.scrolled_process_list(ui, row_height, scroll_index).show_rows(
+            ui,
+            row_height,
+            total_rows,
+            |ui: &mut Ui, row_range: std::ops::Range<usize>| {
+                for index in row_range {
+                    let Some(cached_row) = self.cached_row(index) else {
+                        continue;
+                    };
+                    let Some(row) = cached_row.resolve(&process_data.process_map) else {
+                        continue;
+                    };
+                    let (ListRow::Process(process) | ListRow::Thread(process, _)) = row;
+                    let mut indent = cached_row.depth as f32 * row_height;
+                    let expander = match row {
+                        ListRow::Process(_) if cached_row.is_expandable => Some(self.is_tree_node_open(process.pid)),
+                        // keep the names lined up with the ones behind an arrow
+                        ListRow::Process(_) => {
+                            indent += row_height;
+                            None
+                        }
+                        ListRow::Thread(..) => None,
+                    };
+                    let (response, expander_clicked) =
+                        self.show_process_row(ui, &row, indent, expander, cached_row.totals);
+                    if expander_clicked && !self.toggled_tree_pids.remove(&process.pid) {
+                        self.toggled_tree_pids.insert(process.pid);
+                    }
+                    self.handle_row_response(response, process);
+                }
+            },
+        );
+    }
+    fn handle_row_response(&mut self, response: egui::Response, process: &ProcInfo) {
+        let response = response.interact(egui::Sense::click());
+        if response.clicked() {
+            self.selected_pid = Some(process.pid);
+        }
+        response.context_menu(|ui| {
+            ui.label(RichText::new(format!("{} ({})", process.name, process.pid)).strong());
+            ui.separator();
+            // the pids of another procfs root would name unrelated processes of this machine
+            let is_local = self.proc_root.is_local();
+            for signal in ProcessSignal::ALL {
+                let button = ui.add_enabled(is_local, egui::Button::new(signal.label()));
+                if button.on_disabled_hover_text(NOT_LOCAL_ROOT).clicked() {
+                    self.selected_pid = Some(process.pid);
+                    self.request_signal(process.pid, &process.name, signal);
+                    ui.close_menu();
+                }
+            }
+            ui.separator();
+            let button = ui.add_enabled(is_local, egui::Button::new("Priority and affinity…"));
+            if button.on_disabled_hover_text(NOT_LOCAL_ROOT).clicked() {
+                self.selected_pid = Some(process.pid);
+                self.open_priority_editor(process);
+                ui.close_menu();
+            }
+        });
+    }
+    fn open_priority_editor(&mut self, process: &ProcInfo) {
+        // cpus that are offline now may still be in the affinity
+        let cpu_count = process.cpu_affinity.iter().map(|cpu| cpu + 1).fold(self.core_count, usize::max);
+        let cpus: Vec<bool> = (0..cpu_count).map(|cpu| process.cpu_affinity.contains(&cpu)).collect();
+        let nice = process.nice.clamp(-20, 19) as i32;
+        let io_priority = process.io_priority.unwrap_or_default();
+        self.priority_editor = Some(PriorityEditor {
+            pid: process.pid,
+            name: process.name.clone(),
+            nice,
+            io_priority,
+            cpus: cpus.clone(),
+            applied_nice: nice,
+            applied_io_priority: io_priority,
+            applied_cpus: cpus,
+            errors: Vec::new(),
+        });
+    }
+    fn show_priority_editor(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.priority_editor else {
+            return;
+        };
+        let mut open = true;
+        let mut applied = false;
+        egui::Window::new("Priority and affinity")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(format!("{} ({})", editor.name, editor.pid)).strong());
+                egui::Grid::new("priority_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Nice:");
+                    ui.add(egui::Slider::new(&mut editor.nice, -20..=19))
+                        .on_hover_text("Lower values get more cpu time. Lowering it needs root.");
+                    ui.end_row();
+                    ui.label("I/O class:");
+                    egui::ComboBox::from_id_source("io_priority_class")
+                        .selected_text(editor.io_priority.class.name())
+                        .show_ui(ui, |ui| {
+                            for class in IoPriorityClass::ALL {
+                                ui.selectable_value(&mut editor.io_priority.class, class, class.name());
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("I/O level:");
+                    ui.add_enabled(editor.io_priority.class.has_levels(), egui::Slider::new(&mut editor.io_priority.level, 0..=7))
+                        .on_hover_text("0 is served first");
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Runs on:");
+                    if ui.button("All").clicked() {
+                        editor.cpus.fill(true);
+                    }
+                    if ui.button("None").clicked() {
+                        editor.cpus.fill(false);
+                    }
+                });
+                egui::Grid::new("affinity_grid").show(ui, |ui| {
+                    for (cpu, allowed) in editor.cpus.iter_mut().enumerate() {
+                        ui.checkbox(allowed, format!("CPU {}", cpu));
+                        if cpu % 8 == 7 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+                for error in &editor.errors {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+                ui.separator();
+                let any_cpu = editor.cpus.contains(&true);
+                let apply_button = ui.add_enabled(any_cpu, egui::Button::new("Apply"));
+                if !any_cpu {
+                    ui.label("Pick at least one CPU");
+                }
+                applied = apply_button.clicked();
+            });
+
+        if applied {
+            let mut errors = Vec::new();
+            let mut report = |what: &str, result: taskmanager_core::Result<()>| match result {
+                Ok(()) => true,
+                Err(error) => {
+                    errors.push(format!("Failed to set the {}: {}", what, describe_syscall_error(&error)));
+                    false
+                }
+            };
+            if editor.nice != editor.applied_nice && report("nice value", set_nice(&self.proc_root, editor.pid, editor.nice)) {
+                editor.applied_nice = editor.nice;
+            }
+            if editor.io_priority != editor.applied_io_priority
+                && report("I/O priority", set_io_priority(&self.proc_root, editor.pid, editor.io_priority))
+            {
+                editor.applied_io_priority = editor.io_priority;
+            }
+            if editor.cpus != editor.applied_cpus {
+                let cpus: Vec<usize> = (0..editor.cpus.len()).filter(|cpu| editor.cpus[*cpu]).collect();
+                if report("CPU affinity", set_cpu_affinity(&self.proc_root, editor.pid, &cpus)) {
+                    editor.applied_cpus = editor.cpus.clone();
+                }
+            }
+            // the window stays open with the errors, the values that were set are not set again
+            open = !errors.is_empty();
+            editor.errors = errors;
+        }
+        if !open {
+            self.priority_editor = None;
+        }
+    }
+    fn show_signal_toolbar(&mut self, ui: &mut Ui) {
+        let selected_process = self.selected_pid.and_then(|pid| {
+            self.process_data_mutex
+                .lock()
+                .ok()
+                .and_then(|process_data| process_data.process_map.get(&pid).map(|process| process.name.clone()))
+                .map(|name| (pid, name))
+        });
+
+        ui.horizontal(|ui| {
+            match &selected_process {
+                Some((pid, name)) => ui.label(format!("Selected: {} ({})", name, pid)),
+                None => ui.label("Selected: none"),
+            };
+            for signal in ProcessSignal::ALL {
+                let button = ui.add_enabled(
+                    selected_process.is_some() && self.proc_root.is_local(),
+                    egui::Button::new(signal.label()),
+                );
+                if button.on_disabled_hover_text(NOT_LOCAL_ROOT).clicked() {
+                    if let Some((pid, name)) = &selected_process {
+                        self.request_signal(*pid, name, signal);
+                    }
+                }
+            }
+        });
+    }
+    fn request_signal(&mut self, pid: u32, name: &str, signal: ProcessSignal) {
+        if signal.is_destructive() {
+            self.pending_signal = Some(PendingSignal {
+                pid,
+                name: name.to_string(),
+                signal,
+            });
+        } else {
+            self.dispatch_signal(pid, name, signal);
+        }
+    }
+    fn dispatch_signal(&mut self, pid: u32, name: &str, signal: ProcessSignal) {
+        if let Err(error) = send_signal(pid, signal) {
+            self.signal_error = Some(format!(
+                "Failed to send {} to {} ({}): {}",
+                signal.name(),
+                name,
+                pid,
+                describe_syscall_error(&error)
+            ));
+        }
+    }
+    fn show_signal_dialogs(&mut self, ctx: &egui::Context) {
+        let mut confirmed = None;
+        let mut cancelled = false;
+        if let Some(pending) = &self.pending_signal {
+            egui::Window::new("Confirm signal")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Send {} to {} ({})?",
+                        pending.signal.name(),
+                        pending.name,
+                        pending.pid
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button(pending.signal.label()).clicked() {
+                            confirmed = Some((pending.pid, pending.name.clone(), pending.signal));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancelled = true;
+                        }
+                    });
+                });
+        }
+        if let Some((pid, name, signal)) = confirmed {
+            self.pending_signal = None;
+            self.dispatch_signal(pid, &name, signal);
+        } else if cancelled {
+            self.pending_signal = None;
+        }
+
+        let mut dismissed = false;
+        if let Some(error) = &self.signal_error {
+            egui::Window::new("Signal error")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+                .show(ctx, |ui| {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                    if ui.button("Ok").clicked() {
+                        dismissed = true;
+                    }
+                });
+        }
+        if dismissed {
+            self.signal_error = None;
+        }
+    }
+    fn show_performance(&mut self, ui: &mut Ui) {
+        let cpu_usage = match self.cpu_usage.lock() {
+            Ok(cpu_usage) => Some(cpu_usage.clone()),
+            Err(_) => None,
+        };
+        ui.horizontal(|ui| {
+            if let Some(cpu_usage) = &cpu_usage {
+                ui.label(format!("Total cpu usage: {:.2}%", cpu_usage.total.busy));
+                let progress_bar = egui::ProgressBar::new(cpu_usage.total.busy / 100.0).animate(false);
+                ui.add(progress_bar);
+            } else {
+                ui.label("Unable to get cpu usage!");
+            }
+        });
+        if let Some(cpu_usage) = &cpu_usage {
+            ui.label(cpu_usage.total.summary());
+            self.show_core_grid(ui, &cpu_usage.cores);
+        }
+        ui.horizontal(|ui| {
+            if let Ok(memory_info) = self.memory_info.lock() {
+                ui.label(format!("Total memory used {:.2} GB", kb_to_gb(memory_info.used())));
+                let progress_bar = egui::ProgressBar::new(memory_info.used() as f32 / memory_info.total.max(1) as f32)
+                    .fill(Color32::RED)
+                    .animate(false);
+                ui.add(progress_bar);
+            } else {
+                ui.label("Unable to get memory usage!");
+            }
+        });
+        let memory_info = match self.memory_info.lock() {
+            Ok(memory_info) => memory_info.clone(),
+            Err(_) => MemoryInfo::default(),
+        };
+        self.show_memory_breakdown(ui, &memory_info);
+
+        ui.separator();
+        egui::ComboBox::from_label("History")
+            .selected_text(format!("{} min", self.history_window.as_secs() / 60))
+            .show_ui(ui, |ui| {
+                for window in HISTORY_WINDOWS {
+                    ui.selectable_value(&mut self.history_window, window, format!("{} min", window.as_secs() / 60));
+                }
+            });
+        ui.label("CPU usage");
+        self.show_history_plot(ui, "cpu_history", &self.cpu_history, 100., "%");
+        ui.label("Memory used");
+        self.show_history_plot(ui, "memory_history", &self.memory_history, kb_to_gb(memory_info.total) as f64, "GB");
+
+        ui.separator();
+        self.show_disks(ui);
+        ui.separator();
+        self.show_network(ui);
+    }
+    fn show_disks(&mut self, ui: &mut Ui) {
+        let disks: Vec<DiskUsage> = match self.disk_usage.lock() {
+            Ok(disk_usage) => disk_usage
+                .iter()
+                .filter(|disk| self.show_all_disks || !(disk.is_partition || disk.is_virtual))
+                .cloned()
+                .collect(),
+            Err(_) => {
+                ui.label("Unable to get the disk usage!");
+                return;
+            }
+        };
+        ui.horizontal(|ui| {
+            ui.label("Disks");
+            ui.checkbox(&mut self.show_all_disks, "Show partitions, loop and ram devices");
+        });
+
+        egui::Grid::new("disk_grid").num_columns(7).striped(true).show(ui, |ui| {
+            for title in ["Device", "Read", "Write", "Reads/s", "Writes/s", "Utilization", "Queue"] {
+                ui.label(RichText::new(title).strong());
+            }
+            ui.end_row();
+            for disk in &disks {
+                ui.label(&disk.name);
+                ui.label(format_rate(disk.read_bytes_per_second));
+                ui.label(format_rate(disk.write_bytes_per_second));
+                ui.label(format!("{:.1}", disk.reads_per_second));
+                ui.label(format!("{:.1}", disk.writes_per_second));
+                let progress_bar = egui::ProgressBar::new(disk.utilization / 100.)
+                    .text(format!("{:.1}%", disk.utilization))
+                    .desired_width(120.)
+                    .animate(false);
+                ui.add(progress_bar);
+                ui.label(format!("{:.2}", disk.queue_depth));
+                ui.end_row();
+            }
+        });
+
+        let selected_disk = match &self.selected_disk {
+            Some(name) if disks.iter().any(|disk| &disk.name == name) => name.clone(),
+            _ => match disks.first() {
+                Some(disk) => disk.name.clone(),
+                None => return,
+            },
+        };
+        let mut selected_disk_choice = selected_disk.clone();
+        egui::ComboBox::from_label("Disk throughput")
+            .selected_text(&selected_disk)
+            .show_ui(ui, |ui| {
+                for disk in &disks {
+                    ui.selectable_value(&mut selected_disk_choice, disk.name.clone(), &disk.name);
+                }
+            });
+        self.selected_disk = Some(selected_disk_choice);
+
+        let lines = match self.disk_history.lock() {
+            Ok(disk_history) => match disk_history.get(&selected_disk) {
+                Some(history) => vec![
+                    ("Read", history.read.points(self.history_window)),
+                    ("Write", history.write.points(self.history_window)),
+                ],
+                None => Vec::new(),
+            },
+            Err(_) => {
+                ui.label("Unable to get the history!");
+                return;
+            }
+        };
+        self.show_plot(ui, "disk_history", lines, 0., "MB/s");
+    }
+    fn show_network(&mut self, ui: &mut Ui) {
+        let interfaces: Vec<InterfaceUsage> = match self.network_usage.lock() {
+            Ok(network_usage) => network_usage
+                .iter()
+                .filter(|interface| self.show_all_interfaces || !(interface.is_loopback || interface.is_virtual))
+                .cloned()
+                .collect(),
+            Err(_) => {
+                ui.label("Unable to get the network usage!");
+                return;
+            }
+        };
+        ui.horizontal(|ui| {
+            ui.label("Network");
+            ui.checkbox(&mut self.show_all_interfaces, "Show loopback and virtual interfaces");
+        });
+
+        egui::Grid::new("network_grid").num_columns(7).striped(true).show(ui, |ui| {
+            for title in ["Interface", "Received", "Sent", "Packets in/s", "Packets out/s", "Errors/s", "Drops/s"] {
+                ui.label(RichText::new(title).strong());
+            }
+            ui.end_row();
+            for interface in &interfaces {
+                ui.label(&interface.name);
+                ui.label(format_rate(interface.rx_bytes_per_second));
+                ui.label(format_rate(interface.tx_bytes_per_second));
+                ui.label(format!("{:.1}", interface.rx_packets_per_second));
+                ui.label(format!("{:.1}", interface.tx_packets_per_second));
+                // the rates next to the other rates, the counters since boot on hover
+                ui.label(format!("{:.1}", interface.errors_per_second))
+                    .on_hover_text(format!("{} since boot", interface.errors));
+                ui.label(format!("{:.1}", interface.dropped_per_second))
+                    .on_hover_text(format!("{} since boot", interface.dropped));
+                ui.end_row();
+            }
+        });
+
+        let selected_interface = match &self.selected_interface {
+            Some(name) if interfaces.iter().any(|interface| &interface.name == name) => name.clone(),
+            _ => match interfaces.first() {
+                Some(interface) => interface.name.clone(),
+                None => return,
+            },
+        };
+        let mut selected_interface_choice = selected_interface.clone();
+        egui::ComboBox::from_label("Network throughput")
+            .selected_text(&selected_interface)
+            .show_ui(ui, |ui| {
+                for interface in &interfaces {
+                    ui.selectable_value(&mut selected_interface_choice, interface.name.clone(), &interface.name);
+                }
+            });
+        self.selected_interface = Some(selected_interface_choice);
+
+        let lines = match self.network_history.lock() {
+            Ok(network_history) => match network_history.get(&selected_interface) {
+                Some(history) => vec![
+                    ("Received", history.received.points(self.history_window)),
+                    ("Sent", history.sent.points(self.history_window)),
+                ],
+                None => Vec::new(),
+            },
+            Err(_) => {
+                ui.label("Unable to get the history!");
                 return;
             }
+        };
+        self.show_plot(ui, "network_history", lines, 0., "MB/s");
+    }
+    fn show_memory_breakdown(&self, ui: &mut Ui, memory_info: &MemoryInfo) {
+        let segments = [
+            ("Used", memory_info.used_by_applications(), Color32::from_rgb(220, 60, 60)),
+            ("Buffers", memory_info.buffers, Color32::from_rgb(230, 160, 40)),
+            ("Cached", memory_info.cached, Color32::from_rgb(60, 130, 220)),
+            ("Slab", memory_info.slab, Color32::from_rgb(150, 90, 200)),
+            ("Free", memory_info.free, Color32::from_gray(90)),
+        ];
+
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), 24.),
+            egui::Sense::hover(),
+        );
+        let total = memory_info.total.max(1) as f32;
+        let mut left = rect.left();
+        for (_, value, color) in segments {
+            let width = rect.width() * (value as f32 / total);
+            let segment = egui::Rect::from_min_max(
+                egui::pos2(left, rect.top()),
+                egui::pos2((left + width).min(rect.right()), rect.bottom()),
+            );
+            ui.painter().rect_filled(segment, 0., color);
+            left += width;
+        }
+        response.on_hover_ui(|ui| {
+            for (name, value, _) in segments {
+                ui.label(format!("{}: {:.2} GB", name, kb_to_gb(value)));
+            }
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            for (name, value, color) in segments {
+                let (square, _) = ui.allocate_exact_size(egui::vec2(12., 12.), egui::Sense::hover());
+                ui.painter().rect_filled(square, 2., color);
+                ui.label(format!("{} {:.2} GB", name, kb_to_gb(value)));
+            }
+        });
+        ui.label(format!(
+            "Shared {:.2} GB | Dirty {:.1} MB | Writeback {:.1} MB",
+            kb_to_gb(memory_info.shared),
+            memory_info.dirty as f32 / 1024.,
+            memory_info.writeback as f32 / 1024.
+        ));
+        ui.label(format!(
+            "Swap {:.2} / {:.2} GB | Hugepages {} / {} used ({} kB each)",
+            kb_to_gb(memory_info.swap_used()),
+            kb_to_gb(memory_info.swap_total),
+            memory_info.hugepages_total.saturating_sub(memory_info.hugepages_free),
+            memory_info.hugepages_total,
+            memory_info.hugepage_size
+        ));
+    }
+    fn show_core_grid(&self, ui: &mut Ui, cores: &[CpuBreakdown]) {
+        const CORES_PER_ROW: usize = 4;
+        egui::Grid::new("core_grid").num_columns(CORES_PER_ROW * 2).show(ui, |ui| {
+            for (index, core) in cores.iter().enumerate() {
+                ui.label(format!("CPU{}", index));
+                let progress_bar = egui::ProgressBar::new(core.busy / 100.0)
+                    .text(format!("{:.1}%", core.busy))
+                    .desired_width(120.)
+                    .animate(false);
+                ui.add(progress_bar).on_hover_text(core.summary());
+                if (index + 1) % CORES_PER_ROW == 0 {
+                    ui.end_row();
+                }
+            }
+        });
+    }
+    fn show_history_plot(&self, ui: &mut Ui, id: &str, history: &Mutex<History>, max_value: f64, unit: &'static str) {
+        let points = match history.lock() {
+            Ok(history) => history.points(self.history_window),
+            Err(_) => {
+                ui.label("Unable to get the history!");
+                return;
+            }
+        };
+        self.show_plot(ui, id, vec![("", points)], max_value, unit);
+    }
+    // one graph of the last history_window, with a named line per history; the y axis grows
+    // beyond max_value when the values do
+    fn show_plot(&self, ui: &mut Ui, id: &str, lines: Vec<(&str, Vec<[f64; 2]>)>, max_value: f64, unit: &'static str) {
+        let window_seconds = self.history_window.as_secs_f64();
+
+        egui_plot::Plot::new(id)
+            .height(150.)
+            .include_x(-window_seconds)
+            .include_x(0.)
+            .include_y(0.)
+            .include_y(max_value)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .allow_double_click_reset(false)
+            .x_axis_formatter(|value, _, _| format!("{:.0}s", value))
+            .label_formatter(move |name, point| {
+                let value = format!("{:.0}s ago\n{:.2} {}", -point.x, point.y, unit);
+                if name.is_empty() {
+                    value
+                } else {
+                    format!("{}\n{}", name, value)
+                }
+            })
+            .legend(egui_plot::Legend::default())
+            .show(ui, |plot_ui| {
+                for (name, points) in lines {
+                    plot_ui.line(egui_plot::Line::new(egui_plot::PlotPoints::from(points)).name(name));
+                }
+            });
+    }
+    fn update_process_filter(&mut self) {
+        match ProcessFilter::new(&self.filter_text, self.filter_is_regex) {
+            Ok(process_filter) => {
+                self.process_filter = process_filter;
+                self.filter_error = None;
+            }
+            Err(error) => {
+                // keep the last valid filter while the user is still typing the pattern
+                self.filter_error = Some(format!("Invalid regex: {}", error));
+            }
+        }
+    }
+    fn show_refresh_controls(&mut self, ui: &mut Ui) {
+        let current_rate = self.refresh_control.rate();
+        let mut selected_rate = current_rate;
+        egui::ComboBox::from_label("Refresh")
+            .selected_text(selected_rate.label())
+            .show_ui(ui, |ui| {
+                for interval in REFRESH_INTERVALS {
+                    let rate = RefreshRate::Every(interval);
+                    ui.selectable_value(&mut selected_rate, rate, rate.label());
+                }
+                ui.selectable_value(&mut selected_rate, RefreshRate::Paused, RefreshRate::Paused.label());
+            });
+
+        let pause_message = if current_rate == RefreshRate::Paused { "Resume" } else { "Pause" };
+        if ui.button(pause_message).clicked() {
+            selected_rate = match current_rate {
+                RefreshRate::Paused => RefreshRate::Every(self.resume_interval),
+                RefreshRate::Every(_) => RefreshRate::Paused,
+            };
+        }
+
+        if selected_rate != current_rate {
+            if let RefreshRate::Every(interval) = selected_rate {
+                self.resume_interval = interval;
+            }
+            self.refresh_control.set_rate(selected_rate);
         }
+    }
+    fn parent_chain(&self, pid: u32) -> String {
+        let Ok(process_data) = self.process_data_mutex.lock() else {
+            return String::from(NOT_AVAILABLE);
+        };
+        let process_map = &process_data.process_map;
+        let mut chain = Vec::new();
+        let mut current_pid = pid;
+        while let Some(process) = process_map.get(&current_pid) {
+            chain.push(format!("{} ({})", process.name, process.pid));
+            // stop on pid reuse loops, which can show up between two snapshots
+            if process.parent_pid == current_pid || chain.len() > process_map.len() {
+                break;
+            }
+            current_pid = process.parent_pid;
+        }
+        chain.join(" ← ")
+    }
+    // the thread rows of the list and the tree, and the threads of the details panel
+    fn are_threads_wanted(&self) -> bool {
+        self.tab == Tab::Processes && (self.show_threads || !self.expanded_pids.is_empty() || self.selected_pid.is_some())
+    }
+    fn show_details_panel(&mut self, ctx: &egui::Context) {
+        let Some(pid) = self.selected_pid.filter(|_| self.tab == Tab::Processes) else {
+            return;
+        };
+        let process_details_mutex = self.process_details.clone();
+        let Ok(process_details) = process_details_mutex.lock() else {
+            return;
+        };
+        let loaded_pid = match &*process_details {
+            DetailsLoad::Idle => None,
+            DetailsLoad::Loading(pid) => Some(*pid),
+            DetailsLoad::Done(details) => Some(details.pid),
+        };
+        if loaded_pid != Some(pid) {
+            drop(process_details);
+            self.start_details_load(pid);
+            return;
+        }
+        let parent_chain = self.parent_chain(pid);
+        // the busiest threads first
+        let (mut threads, uptime): (Vec<ThreadInfo>, f32) = match self.process_data_mutex.lock() {
+            Ok(process_data) => (
+                process_data
+                    .process_map
+                    .get(&pid)
+                    .map(|process| process.thread_list.clone())
+                    .unwrap_or_default(),
+                process_data.uptime,
+            ),
+            Err(_) => (Vec::new(), 0.),
+        };
+        threads.sort_by(|first, second| second.cpu.total_cmp(&first.cpu).then(first.tid.cmp(&second.tid)));
+        let mut reload = false;
+        let mut close = false;
 
-        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
-            ui,
-            row_height,
-            total_rows,
-            |ui: &mut Ui, total_rows: std::ops::Range<usize>| {
-                if let Ok(process_map) = self.process_data_mutex.lock() {
-
-                let process_vec: Vec<_> = if !self.show_all_procesess
-                {
-                    process_map.values().filter(|process| process.user != "root").collect()
-
-                }
-                else {
-                     process_map.values().collect()
+        egui::SidePanel::right("process_details")
+            .resizable(true)
+            .default_width(420.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(format!("Process {}", pid));
+                    if ui.button("Refresh").clicked() {
+                        reload = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+                ui.label(format!("Parents: {}", parent_chain));
+                ui.separator();
+                let DetailsLoad::Done(details) = &*process_details else {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Reading the details…");
+                    });
+                    return;
                 };
-                    for i in total_rows {
-                        ui.horizontal(|ui| {
-                            ui.columns(7, |columns| {
-                                if let Some(process) = process_vec.get(i)
-                                {
-
-                                    columns[0].label(RichText::new(process.name.to_string()));
-                                    columns[1].label(RichText::new(process.user.to_string()));
-                                    columns[2].label(RichText::new(format!("{}",process.pid)));
-                                    columns[3].label(RichText::new(process.status.to_string()));
-                                    columns[4]
-                                    .label(RichText::new(format!("{:.2}%", process.cpu)));
-                                columns[5].label(RichText::new(format!(
-                                    "{:.2} Mb",
-                                    process.memory_used
-                                )));
-                                columns[6].label(RichText::new(process.path.to_string()));
+
+                egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                    show_detail(ui, "Command line", &details.command_line, |ui, command_line| {
+                        ui.label(command_line);
+                    });
+                    show_detail(ui, "Working directory", &details.cwd, |ui, cwd| {
+                        ui.label(cwd);
+                    });
+                    show_detail(ui, "Scheduling", &details.stat, |ui, stat| {
+                        let start_time = ProcessStat::ticks_to_seconds(stat.starttime);
+                        ui.label(format!("Nice: {}", stat.nice));
+                        ui.label(format!("Priority: {}", stat.priority));
+                        ui.label(format!("Threads: {}", stat.num_threads));
+                        ui.label(format!("Last ran on CPU {}", stat.processor));
+                        ui.label(format!(
+                            "Started {} after boot, running for {}",
+                            format_duration(start_time),
+                            format_duration(uptime - start_time)
+                        ));
+                    });
+                    show_detail(ui, "Environment", &details.environment, |ui, environment| {
+                        for variable in environment {
+                            ui.label(variable);
+                        }
+                    });
+                    show_detail(ui, "Open files", &details.open_files, |ui, open_files| {
+                        for (fd, target) in open_files {
+                            ui.label(format!("{}: {}", fd, target));
+                        }
+                    });
+                    show_detail(ui, "Sockets", &details.sockets, |ui, sockets| {
+                        if sockets.is_empty() {
+                            ui.label("No sockets");
+                        }
+                        for (fd, socket) in sockets {
+                            let remote = socket.remote();
+                            let ends = if remote.is_empty() {
+                                socket.local()
+                            } else {
+                                format!("{} → {}", socket.local(), remote)
+                            };
+                            ui.label(format!("{}: {} {} {}", fd, socket.protocol.name(), ends, socket.state));
+                        }
+                    });
+                    show_detail(ui, "Memory maps", &details.memory_maps, |ui, maps| {
+                        ui.label(format!("{} mappings, {:.2} MB in total", maps.count, maps.total_kb as f32 / 1024.));
+                        ui.label(format!("File backed: {:.2} MB", maps.file_backed_kb as f32 / 1024.));
+                        ui.label(format!("Anonymous: {:.2} MB", maps.anonymous_kb as f32 / 1024.));
+                        ui.label(format!("Writable: {:.2} MB", maps.writable_kb as f32 / 1024.));
+                    });
+                    egui::CollapsingHeader::new(format!("Threads ({})", threads.len()))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            // they are collected from the next pass on, see are_threads_wanted
+                            if threads.is_empty() {
+                                ui.label("Collecting the threads...");
+                            }
+                            for thread in &threads {
+                                ui.label(format!(
+                                    "{} | {} | {} | {:.2}% | CPU {}",
+                                    thread.tid,
+                                    thread.name,
+                                    thread.state,
+                                    self.displayed_cpu(thread.cpu),
+                                    thread.processor
+                                ));
                             }
-                            });
                         });
-                    }
-                } else {
-                    println!("Error at locking the mutex in the function show_rows_as_list!");
+                    show_detail(ui, "Limits", &details.limits, |ui, limits| {
+                        ui.label(RichText::new(limits).monospace());
+                    });
+                    show_detail(ui, "Cgroup", &details.cgroup, |ui, cgroup| {
+                        ui.label(RichText::new(cgroup).monospace());
+                    });
+                    show_detail(ui, "Namespaces", &details.namespaces, |ui, namespaces| {
+                        for (name, target) in namespaces {
+                            ui.label(format!("{}: {}", name, target));
+                        }
+                    });
+                });
+            });
+
+        drop(process_details);
+        if close {
+            self.selected_pid = None;
+            if let Ok(mut process_details) = self.process_details.lock() {
+                *process_details = DetailsLoad::Idle;
+            }
+        } else if reload {
+            self.start_details_load(pid);
+        }
+    }
+    // The open files, the maps and the socket tables of a busy process take a while to read, so
+    // they are read on their own thread, like the handles in start_handle_search.
+    fn start_details_load(&self, pid: u32) {
+        let process_details = self.process_details.clone();
+        let root = self.proc_root.clone();
+        if let Ok(mut load) = process_details.lock() {
+            *load = DetailsLoad::Loading(pid);
+        }
+        thread::spawn(move || {
+            let details = ProcessDetails::read(&root, pid);
+            if let Ok(mut load) = process_details.lock() {
+                // another process may have been selected in the meantime
+                if matches!(*load, DetailsLoad::Loading(loading_pid) if loading_pid == pid) {
+                    *load = DetailsLoad::Done(Box::new(details));
                 }
-            },
-        );
+            }
+        });
     }
-    fn show_rows_as_tree(&mut self, ui: &mut Ui) {
-        let text_style = egui::TextStyle::Body;
-        let row_height = ui.text_style_height(&text_style);
-        let total_rows = 0;
+    fn connection_rows(&self) -> Vec<(Connection, String)> {
+        let connections = match self.connections.lock() {
+            Ok(connections) => connections.clone(),
+            Err(_) => return Vec::new(),
+        };
+        let process_data = self.process_data_mutex.lock();
+        let process_name = |pid: Option<&u32>| -> String {
+            match (&process_data, pid) {
+                (Ok(process_data), Some(pid)) => process_data
+                    .process_map
+                    .get(pid)
+                    .map_or(String::new(), |process| process.name.clone()),
+                _ => String::new(),
+            }
+        };
+        let filter = self.connection_filter.to_lowercase();
 
+        let mut rows: Vec<(Connection, String)> = connections
+            .into_iter()
+            .filter(|connection| {
+                // an unconnected udp socket is waiting for packets, like a listening tcp socket
+                !self.listening_only || ["LISTEN", "UNCONN"].contains(&connection.socket.state.as_str())
+            })
+            .map(|connection| {
+                let name = process_name(connection.pids.first());
+                (connection, name)
+            })
+            .filter(|(connection, name)| {
+                let socket = &connection.socket;
+                let pids: Vec<String> = connection.pids.iter().map(|pid| pid.to_string()).collect();
+                filter.is_empty()
+                    || [socket.protocol.name(), &socket.local(), &socket.remote(), &socket.state, &pids.join(" "), name]
+                        .iter()
+                        .any(|field| field.to_lowercase().contains(&filter))
+            })
+            .collect();
+        let protocol_index = |protocol: SocketProtocol| SocketProtocol::ALL.iter().position(|other| *other == protocol);
+        rows.sort_by_key(|(connection, _)| {
+            let socket = &connection.socket;
+            (protocol_index(socket.protocol), socket.local_port, socket.inode)
+        });
+        rows
+    }
+    fn show_connections(&mut self, ui: &mut Ui) {
+        const COLUMNS: [(&str, f32); 6] = [
+            ("Protocol", 70.),
+            ("Local address", 260.),
+            ("Remote address", 260.),
+            ("State", 110.),
+            ("PID", 110.),
+            ("Process", 200.),
+        ];
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.connection_filter)
+                    .hint_text("address, port, state, PID or process"),
+            );
+            ui.checkbox(&mut self.listening_only, "Listening only");
+        });
+        let rows = self.connection_rows();
+        if rows.is_empty() && self.connection_filter.is_empty() && !self.listening_only {
+            ui.label("Collecting the connections...");
+            return;
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        ui.horizontal(|ui| {
+            for (title, width) in COLUMNS {
+                show_cell(ui, width, row_height, |ui| ui.label(RichText::new(title).strong()));
+                column_gap(ui, row_height, egui::Sense::hover());
+            }
+        });
         egui::ScrollArea::vertical().auto_shrink(false).show_rows(
             ui,
             row_height,
-            total_rows,
-            |ui: &mut Ui, total_rows: std::ops::Range<usize>| {
-                total_rows.is_empty();
-                if let Ok(process_map) = self.process_data_mutex.lock() {
-                    
-                    let process_vec: Vec<_> = if !self.show_all_procesess
-                    {
-                        process_map.values().filter(|process| process.user != "root").collect()
-                    }
-                    else {
-                        process_map.values().collect()
-                    };
-
-                    for process in &process_vec {
-                        if process_vec.clone().into_iter().any(|proc| process.parent_pid == proc.pid)
-                        {
-                            continue;
+            rows.len(),
+            |ui: &mut Ui, row_range: std::ops::Range<usize>| {
+                for (connection, name) in rows.get(row_range).unwrap_or_default() {
+                    let socket = &connection.socket;
+                    let pids: Vec<String> = connection.pids.iter().map(|pid| pid.to_string()).collect();
+                    let values = [
+                        socket.protocol.name().to_string(),
+                        socket.local(),
+                        socket.remote(),
+                        socket.state.to_string(),
+                        pids.join(", "),
+                        name.to_string(),
+                    ];
+                    let row_response = ui.horizontal(|ui| {
+                        for ((_, width), value) in COLUMNS.iter().zip(values) {
+                            show_cell(ui, *width, row_height, |ui| ui.label(value));
+                            column_gap(ui, row_height, egui::Sense::hover());
                         }
-                        let values: std::collections::btree_map::Values<'_, u32, ProcInfo> =
-                        process_map.values();
-                        self.create_collapse_area(ui, process, values);
+                    });
+                    let Some(pid) = connection.pids.first() else {
+                        continue;
+                    };
+                    // the owner is shown in the process list, with its details next to it
+                    let response = row_response.response.interact(egui::Sense::click());
+                    if response.on_hover_text("Show the process").clicked() {
+                        self.reveal_process(*pid);
                     }
-                } else {
-                    println!("Error at getting process_map from tree view!");
                 }
             },
         );
     }
-    fn create_collapse_area(
-        &self,
-        ui: &mut Ui,
-        process: &ProcInfo,
-        mut values: std::collections::btree_map::Values<'_, u32, ProcInfo>,
-
-    ) {
-        if process.children_processes.is_empty() {
-            ui.label(RichText::new(format!{"{} | {} | {} | {} | {:.2}% | {:.2} Mb | {}",process.name,process.user,process.pid,process.status,process.cpu,process.memory_used,process.path}));
-        } else {
+    // This reads the fd directory and the maps of every process, which takes seconds on a busy
+    // machine, so it runs on its own thread and only on request.
+    fn start_handle_search(&mut self) {
+        let handle_search = self.handle_search.clone();
+        let root = self.proc_root.clone();
+        let pattern = self.handle_pattern.clone();
+        if let Ok(mut search) = handle_search.lock() {
+            *search = HandleSearch::Searching;
+        }
+        thread::spawn(move || {
+            let handles = find_handles(&root, &pattern);
+            if let Ok(mut search) = handle_search.lock() {
+                *search = HandleSearch::Done(handles);
+            }
+        });
+    }
+    // Finds the processes that have a file open or mapped, like `lsof`.
+    fn show_handle_search(&mut self, ctx: &egui::Context) {
+        const COLUMNS: [(&str, f32); 5] = [("PID", 70.), ("Process", 160.), ("Handle", 80.), ("Access", 90.), ("Path", 400.)];
+        let mut open = self.show_handle_search;
+        let mut revealed_pid = None;
+        let mut search_requested = false;
+        let handle_search_mutex = self.handle_search.clone();
+        let Ok(handle_search) = handle_search_mutex.lock() else {
+            println!("Error at locking the handle search!");
+            return;
+        };
+        let is_searching = matches!(*handle_search, HandleSearch::Searching);
+        egui::Window::new("Find handle")
+            .open(&mut open)
+            .default_size([700., 400.])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Path contains:");
+                    let pattern_edit = ui.add(
+                        egui::TextEdit::singleline(&mut self.handle_pattern)
+                            .hint_text("file, directory, device or socket"),
+                    );
+                    let entered = pattern_edit.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    let can_search = !self.handle_pattern.is_empty() && !is_searching;
+                    let find_button = ui.add_enabled(can_search, egui::Button::new("Find"));
+                    search_requested = (find_button.clicked() || entered) && can_search;
+                });
+                let handles = match &*handle_search {
+                    HandleSearch::Idle => return,
+                    HandleSearch::Searching => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Searching…");
+                        });
+                        return;
+                    }
+                    HandleSearch::Done(Err(error)) => {
+                        ui.label(RichText::new(error.to_string()).color(Color32::RED));
+                        return;
+                    }
+                    HandleSearch::Done(Ok(handles)) => handles,
+                };
+                ui.label(format!(
+                    "{} handles found. Processes you may not inspect are left out. Click a row to show the process.",
+                    handles.len()
+                ));
 
-            ui.collapsing(RichText::new(format!{"{} | {} | {} | {} | {:.2}% | {:.2} Mb | {}",process.name,process.user,process.pid,process.status,process.cpu,process.memory_used,process.path}), |ui| {
-                    for child in &process.children_processes
-                    { 
-                        if let Some(child_process) = values.find(|proc_info| { proc_info.pid == *child })
-                        {
-                            self.create_collapse_area(ui, child_process, values.clone());
+                let process_data = self.process_data_mutex.lock();
+                let process_name = |pid: u32| match &process_data {
+                    Ok(process_data) => process_data
+                        .process_map
+                        .get(&pid)
+                        .map_or(String::new(), |process| process.name.clone()),
+                    Err(_) => String::new(),
+                };
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::horizontal().auto_shrink(false).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for (title, width) in COLUMNS {
+                            show_cell(ui, width, row_height, |ui| ui.label(RichText::new(title).strong()));
+                            column_gap(ui, row_height, egui::Sense::hover());
                         }
-                    }
-                
+                    });
+                    egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+                        ui,
+                        row_height,
+                        handles.len(),
+                        |ui: &mut Ui, row_range: std::ops::Range<usize>| {
+                            for handle in handles.get(row_range).unwrap_or_default() {
+                                let (kind, access) = match &handle.kind {
+                                    HandleKind::FileDescriptor(fd, access) => {
+                                        (format!("fd {}", fd), access.map_or("unknown", |access| access.name()).to_string())
+                                    }
+                                    HandleKind::MemoryMap(permissions) => (String::from("mapped"), permissions.clone()),
+                                };
+                                let values = [handle.pid.to_string(), process_name(handle.pid), kind, access, handle.path.clone()];
+                                let row_response = ui.horizontal(|ui| {
+                                    for ((_, width), value) in COLUMNS.iter().zip(values) {
+                                        show_cell(ui, *width, row_height, |ui| ui.label(value));
+                                        column_gap(ui, row_height, egui::Sense::hover());
+                                    }
+                                });
+                                let response = row_response.response.interact(egui::Sense::click());
+                                if response.on_hover_text("Show the process").clicked() {
+                                    revealed_pid = Some(handle.pid);
+                                }
+                            }
+                        },
+                    );
+                });
             });
+        drop(handle_search);
+        self.show_handle_search = open;
+        if search_requested {
+            self.start_handle_search();
+        }
+        if let Some(pid) = revealed_pid {
+            self.reveal_process(pid);
         }
-    }
-    fn show_performance(&self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            if let Ok(locked_value) = self.total_cpu_usage.lock() {
-                ui.label(format!("Total cpu usage: {:.2}%", locked_value));
-                let progress_bar = egui::ProgressBar::new(*locked_value / 100.0).animate(false);
-                ui.add(progress_bar);
-            } else {
-                ui.label("Unable to get cpu usage!");
-            }
-        });
-        ui.horizontal(|ui| {
-            if let Ok(memory_used) = self.memory_info.lock() {
-                ui.label(format!("Total memory used {:.2} GB", memory_used.1));
-                let progress_bar = egui::ProgressBar::new(memory_used.1 / memory_used.0)
-                    .fill(Color32::RED)
-                    .animate(false);
-                ui.add(progress_bar);
-            } else {
-                ui.label("Unable to get memory usage!");
-            }
-        });
     }
     fn show_processes(&mut self, ui: &mut Ui) {
         let mut button_message = String::from("List view");
@@ -230,372 +2149,557 @@ impl App {
                 self.is_list_mode = !self.is_list_mode;
             }
             ui.checkbox(&mut self.show_all_procesess, "Show all processes");
+            ui.checkbox(&mut self.show_threads, "Show threads");
+            ui.menu_button("Columns", |ui| self.show_column_menu(ui));
+            if !self.is_list_mode {
+                if ui.button("Expand all").clicked() {
+                    self.set_all_tree_nodes_open(true);
+                }
+                if ui.button("Collapse all").clicked() {
+                    self.set_all_tree_nodes_open(false);
+                }
+                ui.checkbox(&mut self.show_subtree_totals, "Subtree totals");
+            }
+            ui.label("Filter:");
+            let filter_edit = ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .hint_text("name, user, path or PID"),
+            );
+            let regex_toggle = ui.checkbox(&mut self.filter_is_regex, "Regex");
+            if filter_edit.changed() || regex_toggle.changed() {
+                self.update_process_filter();
+            }
+            if let Some(error) = &self.filter_error {
+                ui.label(RichText::new(error).color(Color32::RED));
+            }
+            egui::ComboBox::from_label("CPU%")
+                .selected_text(self.cpu_mode.label())
+                .show_ui(ui, |ui| {
+                    for cpu_mode in [CpuMode::PerCore, CpuMode::WholeMachine] {
+                        ui.selectable_value(&mut self.cpu_mode, cpu_mode, cpu_mode.label());
+                    }
+                });
         });
+        self.show_signal_toolbar(ui);
 
-        self.create_header_row(ui);
-        if self.is_list_mode {
-            self.show_rows_as_list(ui);
-        } else {
-            self.show_rows_as_tree(ui)
+        // the columns can be wider than the window together, the header scrolls along with the rows
+        egui::ScrollArea::horizontal().auto_shrink(false).show(ui, |ui| {
+            self.create_header_row(ui);
+            if self.is_list_mode {
+                self.show_rows_as_list(ui);
+            } else {
+                self.show_rows_as_tree(ui)
+            }
+        });
+    }
+}
+
+// Lays out one cell of a row of the process list, cutting off whatever does not fit into it.
+fn show_cell<R>(ui: &mut Ui, width: f32, height: f32, add_contents: impl FnOnce(&mut Ui) -> R) -> R {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let mut cell_ui = ui.child_ui(rect, egui::Layout::left_to_right(egui::Align::Center));
+    cell_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+    add_contents(&mut cell_ui)
+}
+
+// The space between two cells, which is the resize handle in the header.
+fn column_gap(ui: &mut Ui, height: f32, sense: egui::Sense) -> (egui::Rect, egui::Response) {
+    ui.allocate_exact_size(egui::vec2(4., height), sense)
+}
+
+// one collapsible section of the details panel, or the reason why it could not be read
+fn show_detail<T>(ui: &mut Ui, title: &str, result: &taskmanager_core::Result<T>, add_contents: impl FnOnce(&mut Ui, &T)) {
+    egui::CollapsingHeader::new(title).default_open(true).show(ui, |ui| match result {
+        Ok(value) => add_contents(ui, value),
+        Err(error) if error.is_permission_denied() => {
+            ui.label(RichText::new(ACCESS_DENIED).color(Color32::RED));
         }
+        Err(error) => {
+            ui.label(RichText::new(error.to_string()).color(Color32::RED));
+        }
+    });
+}
+
+fn format_rate(bytes_per_second: f32) -> String {
+    let units = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_second;
+    let mut unit = 0;
+    while value >= 1024. && unit + 1 < units.len() {
+        value /= 1024.;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, units[unit])
+}
+
+fn format_duration(seconds: f32) -> String {
+    let seconds = seconds.max(0.) as u64;
+    let days = seconds / 86_400;
+    let time = format!("{:02}:{:02}:{:02}", seconds % 86_400 / 3600, seconds % 3600 / 60, seconds % 60);
+    if days > 0 {
+        format!("{}d {}", days, time)
+    } else {
+        time
     }
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         frame.is_web();
+        self.dark_mode = ctx.style().visuals.dark_mode;
+        self.show_details_panel(ctx);
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("Processes").clicked() {
-                    self.is_process_mode = true;
-                }
-                if ui.button("Performance").clicked() {
-                    self.is_process_mode = false;
-                }
+                ui.selectable_value(&mut self.tab, Tab::Processes, "Processes");
+                ui.selectable_value(&mut self.tab, Tab::Performance, "Performance");
+                ui.selectable_value(&mut self.tab, Tab::Connections, "Connections");
+                ui.toggle_value(&mut self.show_handle_search, "Find handle");
+                ui.separator();
+                self.show_refresh_controls(ui);
+                ui.separator();
+                egui::widgets::global_dark_light_mode_switch(ui);
             });
 
-            if self.is_process_mode {
-                self.show_processes(ui);
-            } else {
-                self.show_performance(ui);
+            self.connections_wanted.store(self.tab == Tab::Connections, AtomicOrdering::Relaxed);
+            self.threads_wanted.store(self.are_threads_wanted(), AtomicOrdering::Relaxed);
+            if let Ok(mut process_fields) = self.process_fields.lock() {
+                *process_fields = self.wanted_process_fields();
+            }
+            match self.tab {
+                Tab::Processes => self.show_processes(ui),
+                Tab::Performance => {
+                    egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| self.show_performance(ui));
+                }
+                Tab::Connections => self.show_connections(ui),
             }
             ctx.request_repaint();
         });
+        self.show_handle_search(ctx);
+        self.show_priority_editor(ctx);
+        self.show_signal_dialogs(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.preferences());
     }
 }
 
-struct Info {
-    pid: u32,
-    name: String,
-    status: String,
-    memory_used: u32,
-    parent_pid: u32,
+//################################################################
+const HISTORY_WINDOWS: [Duration; 4] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(10 * 60),
+    Duration::from_secs(30 * 60),
+];
+// enough for the longest window at the fastest refresh rate
+const HISTORY_CAPACITY: usize = 3600;
+
+// Ring buffer with the latest samples of a value, filled by the sampler threads.
+#[derive(Default)]
+struct History {
+    samples: VecDeque<(Instant, f32)>,
 }
-#[derive(Clone)]
-struct ProcInfo {
-    name: String,
-    user: String,
-    pid: u32,
-    status: String,
-    cpu: f32,
-    memory_used: f32,
-    path: String,
-    children_processes: Vec<u32>,
-    parent_pid: u32,
+
+// The throughput of one block device, in MB/s.
+#[derive(Default)]
+struct DiskHistory {
+    read: History,
+    write: History,
 }
-//################################################################
-fn read_process_info(pid: u32) -> io::Result<Info> {
-    let status_path = format!("/proc/{}/status", pid);
-    let status_content = fs::read_to_string(status_path)?;
-
-    let mut name = String::new();
-    let mut status = String::new();
-    let mut memory_used: u32 = 0;
-    let mut parent_pid: u32 = 0;
-
-    for line in status_content.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-
-        if parts.len() >= 2 {
-            match parts[0] {
-                "Name:" => name = parts[1].to_string(),
-                "State:" => status = parts[1].to_string(),
-                "VmRSS:" => {
-                    if let Ok(value) = parts[1].parse::<u32>() {
-                        memory_used = value;
-                    }
-                }
-                "PPid:" => {
-                    if let Ok(value) = parts[1].parse::<u32>() {
-                        parent_pid = value;
-                    }
-                }
-                _ => (),
-            }
+
+// The throughput of one network interface, in MB/s.
+#[derive(Default)]
+struct NetworkHistory {
+    received: History,
+    sent: History,
+}
+
+impl History {
+    fn push(&mut self, value: f32) {
+        if self.samples.len() == HISTORY_CAPACITY {
+            self.samples.pop_front();
         }
+        self.samples.push_back((Instant::now(), value));
     }
 
-    Ok(Info {
-        pid,
-        name,
-        status,
-        memory_used,
-        parent_pid,
-    })
+    // the x coordinate is in seconds relative to the newest sample, so a paused graph stays still
+    fn points(&self, window: Duration) -> Vec<[f64; 2]> {
+        let Some((newest, _)) = self.samples.back() else {
+            return Vec::new();
+        };
+        self.samples
+            .iter()
+            .filter(|(time, _)| newest.duration_since(*time) <= window)
+            .map(|(time, value)| [-newest.duration_since(*time).as_secs_f64(), *value as f64])
+            .collect()
+    }
 }
 //################################################################
-fn read_cpu_usage(prev_total_time: &mut f32, prev_idle_time: &mut f32) -> io::Result<f32> {
-    // read the first line of   /proc/stat
-    let status_content = fs::read_to_string("/proc/stat")?;
-    let cpu_line_info;
-    if let Some(info) = status_content.lines().next() {
-        cpu_line_info = info;
-    } else {
-        println!("Error at getting cpu_line_info!");
-        return Ok(0.);
-    }
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+const REFRESH_INTERVALS: [Duration; 8] = [
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(15),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+];
 
-    let mut idle_time: f32 = 0_f32;
-    let mut total_time: f32 = 0_f32;
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum RefreshRate {
+    Every(Duration),
+    Paused,
+}
 
-    for data in cpu_line_info.split_whitespace().enumerate() {
-        // discard the first word of that first line   (it's always cpu)
-        if data.0 == 0 {
-            continue;
-        }
-        let mut data_as_number: f32 =0.;
-        if let Ok(value) = FromStr::from_str(data.1) {
-            data_as_number = value;
-        }
-        //idle is found at coloumn 5
-        if data.0 == 4 {
-            idle_time = data_as_number;
+impl Default for RefreshRate {
+    fn default() -> Self {
+        RefreshRate::Every(DEFAULT_REFRESH_INTERVAL)
+    }
+}
+
+impl RefreshRate {
+    fn label(self) -> String {
+        match self {
+            RefreshRate::Every(interval) => format!("{}s", interval.as_secs_f32()),
+            RefreshRate::Paused => String::from("Paused"),
         }
-        // sum all of the times found on that first line to get the total time
-        total_time += data_as_number;
     }
+}
 
-    // multiply by   100   to get a percentage
-    let cpu_usage_percentage =
-        (1_f32 - (idle_time - *prev_idle_time) / (total_time - *prev_total_time)) * 100_f32;
+#[derive(Default)]
+struct RefreshSettings {
+    rate: RefreshRate,
+    // bumped on every change, so sleeping collectors can tell that they were woken on purpose
+    generation: u64,
+}
 
-    *prev_idle_time = idle_time;
-    *prev_total_time = total_time;
-    Ok(cpu_usage_percentage)
+// Refresh rate shared by all of the collector threads.
+#[derive(Default)]
+struct RefreshControl {
+    settings: Mutex<RefreshSettings>,
+    changed: Condvar,
 }
-//################################################################
-fn read_memory_usage() -> io::Result<(f32, f32)> {
-    // read the first line of   /proc/stat
-    let status_content = fs::read_to_string("/proc/meminfo")?;
 
-    let mut memory_lines = status_content.lines();
-    let mut total_memory_kb: f32 = 0.;
-    let mut available_memory_kb: f32 = 0.;
-    if let Some(total_memory) = memory_lines.next() {
-        let string_value_option = total_memory.split_whitespace().nth(1);
-        if let Some(string_value) = string_value_option {
-            if let Ok(parsed_memory) = string_value.parse::<f32>() {
-                total_memory_kb = parsed_memory;
-            }
+impl RefreshControl {
+    fn rate(&self) -> RefreshRate {
+        match self.settings.lock() {
+            Ok(settings) => settings.rate,
+            Err(_) => RefreshRate::default(),
         }
     }
 
-    if let Some(available_memory) = memory_lines.nth(1) {
-        let string_value_option = available_memory.split_whitespace().nth(1);
-        if let Some(string_value) = string_value_option {
-            if let Ok(parsed_memory) = string_value.parse::<f32>() {
-                available_memory_kb = parsed_memory;
-            }
+    fn set_rate(&self, rate: RefreshRate) {
+        if let Ok(mut settings) = self.settings.lock() {
+            settings.rate = rate;
+            settings.generation += 1;
+        } else {
+            println!("Error at updating the refresh rate!");
         }
+        self.changed.notify_all();
     }
 
-    Ok((total_memory_kb, total_memory_kb - available_memory_kb))
+    // Blocks until the next collector pass is due: the interval has elapsed, or the rate was
+    // changed to a running one. While paused it blocks until sampling is resumed.
+    fn wait_for_next_pass(&self) {
+        let started = Instant::now();
+        let Ok(mut settings) = self.settings.lock() else {
+            thread::sleep(DEFAULT_REFRESH_INTERVAL);
+            return;
+        };
+        let generation = settings.generation;
+
+        loop {
+            settings = match settings.rate {
+                RefreshRate::Every(_) if settings.generation != generation => return,
+                RefreshRate::Every(interval) => {
+                    let elapsed = started.elapsed();
+                    if elapsed >= interval {
+                        return;
+                    }
+                    match self.changed.wait_timeout(settings, interval - elapsed) {
+                        Ok((settings, _)) => settings,
+                        Err(_) => return,
+                    }
+                }
+                RefreshRate::Paused => match self.changed.wait(settings) {
+                    Ok(settings) => settings,
+                    Err(_) => return,
+                },
+            };
+        }
+    }
 }
 //################################################################
-//https://stackoverflow.com/questions/16726779/how-do-i-get-the-total-cpu-usage-of-an-application-from-proc-pid-stat
-fn get_process_cpu_usage(pid: u32) -> io::Result<f32> {
-    let path = format!("/proc/{}/stat", pid);
-    let stat_file = fs::read_to_string(path)?;
-    let path_uptime = fs::read_to_string("/proc/uptime")?;
+// The processes of the last pass, with their parent to children index.
+#[derive(Default)]
+struct ProcessData {
+    // counts the passes, so the user interface knows when to sort again
+    pass: u64,
+    process_map: BTreeMap<u32, ProcInfo>,
+    tree: ProcessTree,
+    // seconds since boot at the start of the pass
+    uptime: f32,
+}
 
-    let fields: Vec<&str> = stat_file.split_whitespace().collect();
-    let field_uptime: Vec<&str> = path_uptime.split_whitespace().collect();
+// The data shared between the collector threads and the user interface.
+#[derive(Clone)]
+struct Collectors {
+    process_data: Arc<Mutex<ProcessData>>,
+    cpu_usage: Arc<Mutex<CpuUsage>>,
+    memory_info: Arc<Mutex<MemoryInfo>>,
+    cpu_history: Arc<Mutex<History>>,
+    memory_history: Arc<Mutex<History>>,
+    disk_usage: Arc<Mutex<Vec<DiskUsage>>>,
+    disk_history: Arc<Mutex<BTreeMap<String, DiskHistory>>>,
+    network_usage: Arc<Mutex<Vec<InterfaceUsage>>>,
+    network_history: Arc<Mutex<BTreeMap<String, NetworkHistory>>>,
+    connections: Arc<Mutex<Vec<Connection>>>,
+    connections_wanted: Arc<AtomicBool>,
+    threads_wanted: Arc<AtomicBool>,
+    process_fields: Arc<Mutex<ProcessFields>>,
+    refresh_control: Arc<RefreshControl>,
+    proc_root: ProcRoot,
+}
 
-    let mut uptime: f32 = 0.;
-    let mut utime: u32 = 0;
-    let mut stime: u32 = 0;
-    let mut start_time: u32 = 0;
-    if let Ok(value) = field_uptime[0].parse::<f32>() {
-        uptime = value;
-    }
-    if let Ok(value) = fields[12].parse::<u32>() {
-        utime = value;
-    }
-    if let Ok(value) = fields[13].parse::<u32>() {
-        stime = value;
-    }
-    if let Ok(value) = fields[21].parse::<u32>() {
-        start_time = value;
-    }
-    //if we want to include children processes, we need to get fields 14 and 15 too.
+fn spawn_collectors(proc_root: ProcRoot) -> Collectors {
+    let processes_data_mutex = Arc::new(Mutex::new(ProcessData::default()));
+    let processes_data_mutex_clone = processes_data_mutex.clone();
+    let refresh_control = Arc::new(RefreshControl::default());
+    let process_refresh_control = refresh_control.clone();
 
-    let total_time = utime + stime;
+    let threads_wanted = Arc::new(AtomicBool::new(false));
+    let threads_wanted_clone = threads_wanted.clone();
+    // the default columns need none of them
+    let process_fields = Arc::new(Mutex::new(ProcessFields::NONE));
+    let process_fields_clone = process_fields.clone();
 
-    let hertz = procfs::ticks_per_second() as f32;
-    let seconds = uptime - (start_time as f32 / hertz as f32);
+    let process_sampler_root = proc_root.clone();
 
-    Ok(100_f32 * ((total_time as f32 / hertz) / seconds))
-}
-//################################################################
-fn get_process_file_path(pid: u32) -> io::Result<String> {
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(format!("sudo readlink /proc/{}/exe", pid))
-        .output()
-        .expect("Failed to execute command");
-
-    if output.status.success() {
-        let result = String::from_utf8_lossy(&output.stdout).to_string();
-        Ok(result)
-    } else {
-        Ok(String::from("Not found!"))
-    }
-}
-//################################################################
-fn get_process_user_name(pid: u32) -> io::Result<String> {
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(format!("sudo ls -l /proc/{}", pid))
-        .output()
-        .expect("Failed to execute command");
-
-    if output.status.success() {
-        let all_data = String::from_utf8_lossy(&output.stdout).to_string();
-        if let Some(last_line) = all_data.lines().last() {
-            if let Some(user_name) = last_line.split_whitespace().nth(2) {
-                let result = String::from(user_name);
-                Ok(result)
-            }
-            else
-            {
-                Ok(String::from("N/A"))
+    thread::spawn(move || {
+        let mut sampler = Sampler::with_root(process_sampler_root);
+
+        loop {
+            // reading the stat of every thread is expensive, so only while someone looks at them
+            sampler.set_collect_threads(threads_wanted.load(AtomicOrdering::Relaxed));
+            if let Ok(process_fields) = process_fields.lock() {
+                sampler.set_fields(*process_fields);
             }
+            match sampler.processes() {
+                Ok(next_process_map) => {
+                    // the index is built here, once per pass, instead of in every frame
+                    let next_tree = ProcessTree::build(&next_process_map);
+                    if let Ok(mut current_process_data) = processes_data_mutex.lock() {
+                        current_process_data.process_map = next_process_map;
+                        current_process_data.tree = next_tree;
+                        current_process_data.pass += 1;
+                        current_process_data.uptime = sampler.uptime();
+                    } else {
+                        println!("Error at updating process_map!");
+                    }
+                }
+                Err(error) => println!("Error at reading the processes: {}", error),
+            }
+            process_refresh_control.wait_for_next_pass();
         }
-        else
-        {
-            Ok(String::from("N/A"))
-        }
-    } else {
-        Ok(String::from("root"))
-    }
-}
-//################################################################
-fn get_children_processes(proc_info: &mut ProcInfo) -> io::Result<()> {
-    let path = format!("/proc/{}/task/{}/children", proc_info.pid, proc_info.pid);
-    let children_processes = fs::read_to_string(path)?;
+    });
+    let cpu_usage_mutex = Arc::new(Mutex::new(CpuUsage::default()));
+    let cpu_usage_mutex_clone = cpu_usage_mutex.clone();
+    let cpu_refresh_control = refresh_control.clone();
+    let cpu_history = Arc::new(Mutex::new(History::default()));
+    let cpu_history_clone = cpu_history.clone();
 
-    if children_processes.is_empty() {
-        return Ok(());
-    }
+    let cpu_sampler_root = proc_root.clone();
 
-    for child in children_processes.split_whitespace() {
-        match child.parse::<u32>() {
-            Ok(child_pid) => proc_info.children_processes.push(child_pid),
-            Err(_) => println!("Error at getting child pid: {}!", child),
+    thread::spawn(move || {
+        let mut sampler = Sampler::with_root(cpu_sampler_root);
+        loop {
+            if let Ok(cpu_usage) = sampler.cpu_usage() {
+                if let Ok(mut cpu_history) = cpu_history.lock() {
+                    cpu_history.push(cpu_usage.total.busy);
+                }
+                if let Ok(mut current_cpu_usage) = cpu_usage_mutex.lock() {
+                    *current_cpu_usage = cpu_usage;
+                }
+            }
+            cpu_refresh_control.wait_for_next_pass();
         }
-    }
+    });
 
-    Ok(())
-}
-//################################################################
-fn get_process_data(pid: u32) -> ProcInfo {
-    let mut proc_info: ProcInfo = ProcInfo {
-        name: String::from(""),
-        user: String::from(""),
-        pid: 0,
-        status: String::from(""),
-        cpu: 0.,
-        memory_used: 0.,
-        path: String::from(""),
-        children_processes: Vec::new(),
-        parent_pid: 0,
-    };
+    let memory_info_mutex = Arc::new(Mutex::new(MemoryInfo::default()));
+    let memory_info_mutex_clone = memory_info_mutex.clone();
+    let memory_refresh_control = refresh_control.clone();
+    let memory_history = Arc::new(Mutex::new(History::default()));
+    let memory_history_clone = memory_history.clone();
 
-    if let Ok(info) = read_process_info(pid) {
-        proc_info.status = info.status;
-        proc_info.pid = info.pid;
-        proc_info.memory_used = info.memory_used as f32 / 1024.0;
-        proc_info.name = info.name;
-        proc_info.parent_pid = info.parent_pid;
-    }
-    if let Ok(process_cpu_usage) = get_process_cpu_usage(pid) {
-        proc_info.cpu = process_cpu_usage;
-    }
-    if let Ok(user_name) = get_process_user_name(pid) {
-        proc_info.user = user_name;
-    }
-    if let Ok(file_path) = get_process_file_path(pid) {
-        proc_info.path = file_path;
-    }
-    if let Err(error) = get_children_processes(&mut proc_info) {
-        println!("Error at get_children_processes: {}", error);
-    }
+    let memory_sampler = Sampler::with_root(proc_root.clone());
 
-    proc_info
-}
-//################################################################
+    thread::spawn(move || loop {
+        if let Ok(memory_info) = memory_sampler.memory_info() {
+            if let Ok(mut memory_history) = memory_history.lock() {
+                memory_history.push(kb_to_gb(memory_info.used()));
+            }
+            if let Ok(mut current_memory_info) = memory_info_mutex.lock() {
+                *current_memory_info = memory_info;
+            }
+        }
 
-fn main() {
-    let processes_data: BTreeMap<u32, ProcInfo> = BTreeMap::new();
-    let processes_data_mutex = Arc::new(Mutex::new(processes_data));
-    let processes_data_mutex_clone = processes_data_mutex.clone();
+        memory_refresh_control.wait_for_next_pass();
+    });
 
-    thread::spawn(move || {
-        let proc_path = "/proc";
+    let disk_usage_mutex = Arc::new(Mutex::new(Vec::new()));
+    let disk_usage_mutex_clone = disk_usage_mutex.clone();
+    let disk_refresh_control = refresh_control.clone();
+    let disk_history = Arc::new(Mutex::new(BTreeMap::new()));
+    let disk_history_clone = disk_history.clone();
 
-        loop {
-            let mut next_process_map: BTreeMap<u32, ProcInfo> = BTreeMap::new();
+    let disk_sampler_root = proc_root.clone();
 
-            if let Ok(entries) = fs::read_dir(proc_path) {
-                for entry in entries.filter_map(|e| e.ok()) {
-                    if let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() {
-                        let proc_info = get_process_data(pid);
-                        next_process_map.insert(pid, proc_info);
+    thread::spawn(move || {
+        let mut sampler = Sampler::with_root(disk_sampler_root);
+        // the devices of the previous pass, the others were measured since boot
+        let mut measured_disks: HashSet<String> = HashSet::new();
+        loop {
+            match sampler.disk_usage() {
+                Ok(disk_usage) => {
+                    if let Ok(mut disk_history) = disk_history.lock() {
+                        for disk in &disk_usage {
+                            let history: &mut DiskHistory = disk_history.entry(disk.name.clone()).or_default();
+                            if measured_disks.contains(&disk.name) {
+                                history.read.push(disk.read_bytes_per_second / 1024. / 1024.);
+                                history.write.push(disk.write_bytes_per_second / 1024. / 1024.);
+                            }
+                        }
+                        // removed devices, like loop devices and usb disks
+                        disk_history.retain(|name, _| disk_usage.iter().any(|disk| &disk.name == name));
+                    }
+                    measured_disks = disk_usage.iter().map(|disk| disk.name.clone()).collect();
+                    if let Ok(mut current_disk_usage) = disk_usage_mutex.lock() {
+                        *current_disk_usage = disk_usage;
                     }
                 }
+                Err(error) => println!("Error at reading the disk usage: {}", error),
             }
-            if let Ok(mut current_process_map) = processes_data_mutex.lock() {
-                current_process_map.clear();
-                current_process_map.append(&mut next_process_map);
-            } else {
-                println!("Error at updating process_map!");
-            }
-            thread::sleep(Duration::new(10, 0));
+            disk_refresh_control.wait_for_next_pass();
         }
     });
-    let total_cpu_usage_mutex = Arc::new(Mutex::new(0.));
-    let total_cpu_usage_mutex_clone = total_cpu_usage_mutex.clone();
+
+    let network_usage_mutex = Arc::new(Mutex::new(Vec::new()));
+    let network_usage_mutex_clone = network_usage_mutex.clone();
+    let network_refresh_control = refresh_control.clone();
+    let network_history = Arc::new(Mutex::new(BTreeMap::new()));
+    let network_history_clone = network_history.clone();
+
+    let network_sampler_root = proc_root.clone();
 
     thread::spawn(move || {
-        let mut previous_cpu_usage = 0_f32;
-        let mut previous_idle_time = 0_f32;
+        let mut sampler = Sampler::with_root(network_sampler_root);
+        // the interfaces of the previous pass, the others were measured since boot
+        let mut measured_interfaces: HashSet<String> = HashSet::new();
         loop {
-            if let Ok(total_cpu) = read_cpu_usage(&mut previous_cpu_usage, &mut previous_idle_time)
-            {
-                if let Ok(mut total_cpu_usage) = total_cpu_usage_mutex.lock() {
-                    *total_cpu_usage = total_cpu;
+            match sampler.network_usage() {
+                Ok(network_usage) => {
+                    if let Ok(mut network_history) = network_history.lock() {
+                        for interface in &network_usage {
+                            let history: &mut NetworkHistory = network_history.entry(interface.name.clone()).or_default();
+                            if measured_interfaces.contains(&interface.name) {
+                                history.received.push(interface.rx_bytes_per_second / 1024. / 1024.);
+                                history.sent.push(interface.tx_bytes_per_second / 1024. / 1024.);
+                            }
+                        }
+                        // removed interfaces, like the veth of a stopped container
+                        network_history.retain(|name, _| network_usage.iter().any(|interface| &interface.name == name));
+                    }
+                    measured_interfaces = network_usage.iter().map(|interface| interface.name.clone()).collect();
+                    if let Ok(mut current_network_usage) = network_usage_mutex.lock() {
+                        *current_network_usage = network_usage;
+                    }
                 }
+                Err(error) => println!("Error at reading the network usage: {}", error),
             }
-            thread::sleep(Duration::new(2, 0));
+            network_refresh_control.wait_for_next_pass();
         }
     });
 
-    let total_memory_used_mutex = Arc::new(Mutex::new((0., 0.)));
-    let total_memory_used_mutex_clone = total_memory_used_mutex.clone();
+    let connections_mutex = Arc::new(Mutex::new(Vec::new()));
+    let connections_mutex_clone = connections_mutex.clone();
+    let connections_refresh_control = refresh_control.clone();
+    let connections_wanted = Arc::new(AtomicBool::new(false));
+    let connections_wanted_clone = connections_wanted.clone();
+
+    let connections_root = proc_root.clone();
 
+    // this reads the fd directory of every process, so it only runs while someone looks
     thread::spawn(move || loop {
-        if let Ok((total_memory, used_memory)) = read_memory_usage() {
-            if let Ok(mut total_memory_used) = total_memory_used_mutex.lock() {
-                total_memory_used.0 = total_memory / 1_048_576.0;
-                total_memory_used.1 = used_memory / 1_048_576.0;
+        if connections_wanted.load(AtomicOrdering::Relaxed) {
+            match read_connections(&connections_root) {
+                Ok(connections) => {
+                    if let Ok(mut current_connections) = connections_mutex.lock() {
+                        *current_connections = connections;
+                    }
+                }
+                Err(error) => println!("Error at reading the connections: {}", error),
             }
         }
-
-        thread::sleep(Duration::new(2, 0));
+        connections_refresh_control.wait_for_next_pass();
     });
 
+    Collectors {
+        process_data: processes_data_mutex_clone,
+        cpu_usage: cpu_usage_mutex_clone,
+        memory_info: memory_info_mutex_clone,
+        cpu_history: cpu_history_clone,
+        memory_history: memory_history_clone,
+        disk_usage: disk_usage_mutex_clone,
+        disk_history: disk_history_clone,
+        network_usage: network_usage_mutex_clone,
+        network_history: network_history_clone,
+        connections: connections_mutex_clone,
+        connections_wanted: connections_wanted_clone,
+        threads_wanted: threads_wanted_clone,
+        process_fields: process_fields_clone,
+        refresh_control,
+        proc_root,
+    }
+}
+
+fn main() {
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("Error: {}\n\n{}", error, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match args.mode {
+        cli::Mode::Gui => (),
+        cli::Mode::Help => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        cli::Mode::Snapshot(options) => {
+            if let Err(error) = cli::run_snapshot(&options, args.proc_root) {
+                eprintln!("Error: {}", error);
+                std::process::exit(1);
+            }
+            return;
+        }
+        cli::Mode::Tui => {
+            if let Err(error) = tui::run(spawn_collectors(args.proc_root)) {
+                println!("Error: {}", error);
+            }
+            return;
+        }
+    }
+
+    let collectors = spawn_collectors(args.proc_root);
+
     let native_options = NativeOptions::default();
     match eframe::run_native(
         "Task Manager",
         native_options,
-        Box::new(move |cc| {
-            Box::new(App::new(
-                cc,
-                processes_data_mutex_clone,
-                total_cpu_usage_mutex_clone,
-                total_memory_used_mutex_clone,
-            ))
-        }),
+        Box::new(move |cc| Box::new(App::new(cc, collectors))),
     ) {
         Ok(()) => println!("Running!"),
         Err(error) => println!("Error: {}", error),
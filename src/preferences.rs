@@ -0,0 +1,97 @@
+use crate::CpuMode;
+use crate::RefreshRate;
+use crate::SortColumn;
+use crate::HISTORY_WINDOWS;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+pub const MIN_COLUMN_WIDTH: f32 = 30.;
+
+// The settings that are saved when the task manager is closed and restored on the next start.
+// The size of the window is saved by eframe itself.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub is_list_mode: bool,
+    pub show_all_processes: bool,
+    pub show_threads: bool,
+    pub show_subtree_totals: bool,
+    pub columns: Vec<ColumnSettings>,
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    pub refresh_rate: RefreshRate,
+    pub cpu_mode: CpuMode,
+    pub history_window: Duration,
+    pub dark_mode: bool,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            is_list_mode: true,
+            show_all_processes: false,
+            show_threads: false,
+            show_subtree_totals: false,
+            columns: default_columns(),
+            sort_column: SortColumn::Pid,
+            sort_descending: false,
+            refresh_rate: RefreshRate::default(),
+            cpu_mode: CpuMode::PerCore,
+            history_window: HISTORY_WINDOWS[0],
+            dark_mode: true,
+        }
+    }
+}
+
+impl Preferences {
+    // Settings written by another version can name columns twice, miss some or hold values
+    // that the controls do not offer, so they are brought back in range after loading.
+    pub fn normalize(&mut self) {
+        let mut columns: Vec<ColumnSettings> = Vec::with_capacity(self.columns.len());
+        for settings in &self.columns {
+            if !columns.iter().any(|known| known.column == settings.column) {
+                columns.push(*settings);
+            }
+        }
+        for settings in default_columns() {
+            if !columns.iter().any(|known| known.column == settings.column) {
+                columns.push(settings);
+            }
+        }
+        for settings in &mut columns {
+            settings.width = settings.width.max(MIN_COLUMN_WIDTH);
+            // without the name there is nothing to click on to select a process
+            settings.visible |= settings.column == SortColumn::Name;
+        }
+        self.columns = columns;
+
+        if !HISTORY_WINDOWS.contains(&self.history_window) {
+            self.history_window = HISTORY_WINDOWS[0];
+        }
+        if let RefreshRate::Every(interval) = self.refresh_rate {
+            if interval.is_zero() {
+                self.refresh_rate = RefreshRate::default();
+            }
+        }
+    }
+}
+
+// The position, width and visibility of one column of the process list.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ColumnSettings {
+    pub column: SortColumn,
+    pub visible: bool,
+    pub width: f32,
+}
+
+pub fn default_columns() -> Vec<ColumnSettings> {
+    SortColumn::ALL
+        .into_iter()
+        .map(|column| ColumnSettings {
+            column,
+            visible: SortColumn::DEFAULT.contains(&column),
+            width: column.default_width(),
+        })
+        .collect()
+}
@@ -0,0 +1,448 @@
+use crate::is_process_shown;
+use crate::sort_processes;
+use crate::with_ancestors;
+use crate::Collectors;
+use crate::ProcessData;
+use crate::ProcessFilter;
+use crate::RefreshRate;
+use crate::SortColumn;
+use crate::DEFAULT_REFRESH_INTERVAL;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Gauge, LineGauge, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use std::io;
+use std::time::Duration;
+use taskmanager_core::describe_syscall_error;
+use taskmanager_core::kb_to_gb;
+use taskmanager_core::send_signal;
+use taskmanager_core::CpuUsage;
+use taskmanager_core::MemoryInfo;
+use taskmanager_core::ProcInfo;
+use taskmanager_core::ProcessSignal;
+
+const HELP: &str = "q quit | Tab tabs | t tree | a all | / filter | 1-7 sort | T term | K kill | S stop | C cont | p pause";
+
+// One line of the process table. In tree mode the name is indented by its depth.
+struct TuiRow<'a> {
+    process: &'a ProcInfo,
+    depth: usize,
+    has_children: bool,
+}
+
+enum InputMode {
+    Normal,
+    Filter,
+    Confirm(u32, String, ProcessSignal),
+}
+
+struct TuiApp {
+    collectors: Collectors,
+    is_list_mode: bool,
+    is_process_mode: bool,
+    show_all_processes: bool,
+    sort_column: SortColumn,
+    sort_descending: bool,
+    filter_text: String,
+    selected_pid: Option<u32>,
+    row_pids: Vec<u32>,
+    collapsed_pids: HashSet<u32>,
+    table_state: TableState,
+    input_mode: InputMode,
+    status_message: Option<String>,
+    resume_interval: Duration,
+    should_quit: bool,
+}
+
+// Runs the terminal interface on top of the already running collector threads.
+pub fn run(collectors: Collectors) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = TuiApp::new(collectors).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl TuiApp {
+    fn new(collectors: Collectors) -> Self {
+        TuiApp {
+            collectors,
+            is_list_mode: true,
+            is_process_mode: true,
+            show_all_processes: false,
+            sort_column: SortColumn::Pid,
+            sort_descending: false,
+            filter_text: String::new(),
+            selected_pid: None,
+            row_pids: Vec::new(),
+            collapsed_pids: HashSet::new(),
+            table_state: TableState::default(),
+            input_mode: InputMode::Normal,
+            status_message: None,
+            resume_interval: DEFAULT_REFRESH_INTERVAL,
+            should_quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.should_quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            // redraw at least a few times per second so new snapshots show up
+            if event::poll(Duration::from_millis(250))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    //################################################################
+    fn handle_key(&mut self, key: KeyEvent) {
+        match &self.input_mode {
+            InputMode::Filter => match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.input_mode = InputMode::Normal,
+                KeyCode::Backspace => {
+                    self.filter_text.pop();
+                }
+                KeyCode::Char(character) => self.filter_text.push(character),
+                _ => (),
+            },
+            InputMode::Confirm(pid, name, signal) => {
+                if let KeyCode::Char('y') | KeyCode::Char('Y') = key.code {
+                    let (pid, name, signal) = (*pid, name.clone(), *signal);
+                    self.dispatch_signal(pid, &name, signal);
+                }
+                self.input_mode = InputMode::Normal;
+            }
+            InputMode::Normal => self.handle_normal_key(key),
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        self.status_message = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Tab => self.is_process_mode = !self.is_process_mode,
+            KeyCode::Char('t') => self.is_list_mode = !self.is_list_mode,
+            KeyCode::Char('a') => self.show_all_processes = !self.show_all_processes,
+            KeyCode::Char('/') => self.input_mode = InputMode::Filter,
+            KeyCode::Char('p') => self.toggle_pause(),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-20),
+            KeyCode::PageDown => self.move_selection(20),
+            KeyCode::Home => self.move_selection(isize::MIN / 2),
+            KeyCode::End => self.move_selection(isize::MAX / 2),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(pid) = self.selected_pid {
+                    if !self.collapsed_pids.remove(&pid) {
+                        self.collapsed_pids.insert(pid);
+                    }
+                }
+            }
+            KeyCode::Char(digit @ '1'..='7') => {
+                let column = SortColumn::DEFAULT[digit as usize - '1' as usize];
+                if self.sort_column == column {
+                    self.sort_descending = !self.sort_descending;
+                } else {
+                    self.sort_column = column;
+                    self.sort_descending = false;
+                }
+            }
+            KeyCode::Char('T') => self.request_signal(ProcessSignal::Terminate),
+            KeyCode::Char('K') => self.request_signal(ProcessSignal::Kill),
+            KeyCode::Char('S') => self.request_signal(ProcessSignal::Stop),
+            KeyCode::Char('C') => self.request_signal(ProcessSignal::Continue),
+            _ => (),
+        }
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.row_pids.is_empty() {
+            return;
+        }
+        let current = self
+            .selected_pid
+            .and_then(|pid| self.row_pids.iter().position(|row_pid| *row_pid == pid))
+            .unwrap_or(0);
+        let next = current.saturating_add_signed(offset).min(self.row_pids.len() - 1);
+        self.selected_pid = Some(self.row_pids[next]);
+    }
+
+    fn toggle_pause(&mut self) {
+        let refresh_control = &self.collectors.refresh_control;
+        match refresh_control.rate() {
+            RefreshRate::Every(interval) => {
+                self.resume_interval = interval;
+                refresh_control.set_rate(RefreshRate::Paused);
+            }
+            RefreshRate::Paused => refresh_control.set_rate(RefreshRate::Every(self.resume_interval)),
+        }
+    }
+
+    fn request_signal(&mut self, signal: ProcessSignal) {
+        let Some(pid) = self.selected_pid else {
+            return;
+        };
+        // the pids of another procfs root would name unrelated processes of this machine
+        if !self.collectors.proc_root.is_local() {
+            self.status_message = Some(String::from("Signals can only be sent with the local /proc"));
+            return;
+        }
+        let name = match self.collectors.process_data.lock() {
+            Ok(process_data) => process_data.process_map.get(&pid).map(|process| process.name.clone()),
+            Err(_) => None,
+        }
+        .unwrap_or_default();
+
+        if signal.is_destructive() {
+            self.input_mode = InputMode::Confirm(pid, name, signal);
+        } else {
+            self.dispatch_signal(pid, &name, signal);
+        }
+    }
+
+    fn dispatch_signal(&mut self, pid: u32, name: &str, signal: ProcessSignal) {
+        self.status_message = Some(match send_signal(pid, signal) {
+            Ok(()) => format!("Sent {} to {} ({})", signal.name(), name, pid),
+            Err(error) => format!(
+                "Failed to send {} to {} ({}): {}",
+                signal.name(),
+                name,
+                pid,
+                describe_syscall_error(&error)
+            ),
+        });
+    }
+    //################################################################
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, main_area, footer_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let tabs = Tabs::new(vec!["Processes", "Performance"])
+            .select(if self.is_process_mode { 0 } else { 1 })
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_widget(tabs, tabs_area);
+
+        if self.is_process_mode {
+            self.draw_processes(frame, main_area);
+        } else {
+            self.draw_performance(frame, main_area);
+        }
+
+        let footer = match &self.input_mode {
+            InputMode::Filter => format!("Filter: {}_", self.filter_text),
+            InputMode::Confirm(pid, name, signal) => {
+                format!("Send {} to {} ({})? y/n", signal.name(), name, pid)
+            }
+            InputMode::Normal => match &self.status_message {
+                Some(message) => message.clone(),
+                None => format!("Refresh: {} | {}", self.collectors.refresh_control.rate().label(), HELP),
+            },
+        };
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+
+    fn collect_rows<'a>(&self, process_data: &'a ProcessData) -> Vec<TuiRow<'a>> {
+        let process_map = &process_data.process_map;
+        let process_filter = ProcessFilter::new(&self.filter_text, false).ok().flatten();
+        let mut shown: Vec<&ProcInfo> = process_map
+            .values()
+            .filter(|process| is_process_shown(process, self.show_all_processes, process_filter.as_ref()))
+            .collect();
+
+        if self.is_list_mode {
+            sort_processes(&mut shown, self.sort_column, self.sort_descending);
+            return shown
+                .into_iter()
+                .map(|process| TuiRow {
+                    process,
+                    depth: 0,
+                    has_children: false,
+                })
+                .collect();
+        }
+
+        let visible_pids: HashSet<u32> = if process_filter.is_some() {
+            with_ancestors(process_map, shown.iter().map(|process| process.pid))
+        } else {
+            shown.iter().map(|process| process.pid).collect()
+        };
+        let tree = &process_data.tree;
+        // the visible processes right below `pids`, the children of a hidden process take its place
+        let visible_below = |pids: &[u32]| -> Vec<&'a ProcInfo> {
+            let mut below = Vec::new();
+            let mut pending = pids.to_vec();
+            while let Some(pid) = pending.pop() {
+                let Some(process) = process_map.get(&pid) else {
+                    continue;
+                };
+                if visible_pids.contains(&pid) {
+                    below.push(process);
+                } else {
+                    pending.extend_from_slice(tree.children(pid));
+                }
+            }
+            sort_processes(&mut below, self.sort_column, self.sort_descending);
+            below
+        };
+
+        let mut rows = Vec::new();
+        let mut stack: Vec<(&ProcInfo, usize)> = visible_below(tree.roots()).into_iter().rev().map(|root| (root, 0)).collect();
+        while let Some((process, depth)) = stack.pop() {
+            let children = visible_below(tree.children(process.pid));
+            rows.push(TuiRow {
+                process,
+                depth,
+                has_children: !children.is_empty(),
+            });
+            if !self.collapsed_pids.contains(&process.pid) {
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+        }
+        rows
+    }
+
+    fn draw_processes(&mut self, frame: &mut Frame, area: Rect) {
+        let process_data_mutex = self.collectors.process_data.clone();
+        let Ok(process_data) = process_data_mutex.lock() else {
+            return;
+        };
+        let rows = self.collect_rows(&process_data);
+        self.row_pids = rows.iter().map(|row| row.process.pid).collect();
+        if self.selected_pid.is_none() {
+            self.selected_pid = self.row_pids.first().copied();
+        }
+        let selected_index = self
+            .selected_pid
+            .and_then(|pid| self.row_pids.iter().position(|row_pid| *row_pid == pid));
+        self.table_state.select(selected_index);
+
+        let header = Row::new(SortColumn::DEFAULT.iter().map(|column| {
+            let mut title = String::from(column.title());
+            if *column == self.sort_column {
+                title.push_str(if self.sort_descending { " ▼" } else { " ▲" });
+            }
+            Cell::from(title)
+        }))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let table_rows = rows.iter().map(|row| {
+            let process = row.process;
+            let marker = match (row.has_children, self.collapsed_pids.contains(&process.pid)) {
+                (false, _) => "  ",
+                (true, true) => "▸ ",
+                (true, false) => "▾ ",
+            };
+            let name = if self.is_list_mode {
+                process.name.clone()
+            } else {
+                format!("{}{}{}", "  ".repeat(row.depth), marker, process.name)
+            };
+            Row::new(vec![
+                Cell::from(name),
+                Cell::from(process.user.clone()),
+                Cell::from(process.pid.to_string()),
+                Cell::from(process.status.clone()),
+                Cell::from(format!("{:.2}%", process.cpu)),
+                Cell::from(format!("{:.2} Mb", process.memory_used)),
+                Cell::from(process.path.clone()),
+            ])
+        });
+
+        let widths = [
+            Constraint::Percentage(20),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(12),
+            Constraint::Min(10),
+        ];
+        let title = if self.is_list_mode { " List view " } else { " Tree view " };
+        let table = Table::new(table_rows, widths)
+            .header(header)
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    fn draw_performance(&self, frame: &mut Frame, area: Rect) {
+        let cpu_usage = match self.collectors.cpu_usage.lock() {
+            Ok(cpu_usage) => cpu_usage.clone(),
+            Err(_) => CpuUsage::default(),
+        };
+        let memory_info = match self.collectors.memory_info.lock() {
+            Ok(memory_info) => memory_info.clone(),
+            Err(_) => MemoryInfo::default(),
+        };
+
+        let [cpu_area, breakdown_area, memory_area, swap_area, cores_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+
+        let cpu_gauge = Gauge::default()
+            .block(Block::bordered().title(" CPU "))
+            .gauge_style(Style::default().fg(Color::Green))
+            .ratio((cpu_usage.total.busy / 100.).clamp(0., 1.) as f64)
+            .label(format!("{:.2}%", cpu_usage.total.busy));
+        frame.render_widget(cpu_gauge, cpu_area);
+        frame.render_widget(Paragraph::new(cpu_usage.total.summary()), breakdown_area);
+
+        let memory_gauge = Gauge::default()
+            .block(Block::bordered().title(" Memory "))
+            .gauge_style(Style::default().fg(Color::Red))
+            .ratio((memory_info.used() as f64 / memory_info.total.max(1) as f64).clamp(0., 1.))
+            .label(format!(
+                "{:.2} / {:.2} GB",
+                kb_to_gb(memory_info.used()),
+                kb_to_gb(memory_info.total)
+            ));
+        frame.render_widget(memory_gauge, memory_area);
+
+        let swap_gauge = Gauge::default()
+            .block(Block::bordered().title(" Swap "))
+            .gauge_style(Style::default().fg(Color::Magenta))
+            .ratio((memory_info.swap_used() as f64 / memory_info.swap_total.max(1) as f64).clamp(0., 1.))
+            .label(format!(
+                "{:.2} / {:.2} GB",
+                kb_to_gb(memory_info.swap_used()),
+                kb_to_gb(memory_info.swap_total)
+            ));
+        frame.render_widget(swap_gauge, swap_area);
+
+        // two columns of per-core gauges
+        let cores_block = Block::bordered().title(" Cores ");
+        let inner_area = cores_block.inner(cores_area);
+        frame.render_widget(cores_block, cores_area);
+        let [left_area, right_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(inner_area);
+        let rows_per_column = cpu_usage.cores.len().div_ceil(2);
+        for (index, core) in cpu_usage.cores.iter().enumerate() {
+            let column_area = if index < rows_per_column { left_area } else { right_area };
+            let row = (index % rows_per_column.max(1)) as u16;
+            if row >= column_area.height {
+                continue;
+            }
+            let gauge_area = Rect::new(column_area.x, column_area.y + row, column_area.width, 1);
+            let gauge = LineGauge::default()
+                .filled_style(Style::default().fg(Color::Green))
+                .ratio((core.busy / 100.).clamp(0., 1.) as f64)
+                .label(Line::from(format!("CPU{:<3} {:>5.1}%", index, core.busy)));
+            frame.render_widget(gauge, gauge_area);
+        }
+    }
+}
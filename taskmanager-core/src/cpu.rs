@@ -0,0 +1,188 @@
+use crate::error::{Error, Result};
+use crate::files;
+use crate::root::ProcRoot;
+
+/// One cpu line of `/proc/stat`, in clock ticks.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CpuTimes {
+    /// Time spent in user mode.
+    pub user: u64,
+    /// Time spent in user mode with a positive nice value.
+    pub nice: u64,
+    /// Time spent in kernel mode.
+    pub system: u64,
+    /// Time spent idle.
+    pub idle: u64,
+    /// Time spent idle while waiting for io.
+    pub iowait: u64,
+    /// Time spent servicing interrupts.
+    pub irq: u64,
+    /// Time spent servicing softirqs.
+    pub softirq: u64,
+    /// Time stolen by the hypervisor for other virtual machines.
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    /// Parses a `cpu` or `cpuN` line of `/proc/stat`. Missing values are read as 0.
+    pub fn parse(cpu_line: &str) -> Self {
+        // discard the first word of the line (cpu or cpuN)
+        let mut values = cpu_line
+            .split_whitespace()
+            .skip(1)
+            .map(|value| value.parse::<u64>().unwrap_or(0));
+        let mut next_value = || values.next().unwrap_or(0);
+        // guest and guest_nice come after steal and are already included in user and nice
+        CpuTimes {
+            user: next_value(),
+            nice: next_value(),
+            system: next_value(),
+            idle: next_value(),
+            iowait: next_value(),
+            irq: next_value(),
+            softirq: next_value(),
+            steal: next_value(),
+        }
+    }
+
+    /// The sum of all of the states.
+    pub fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+}
+
+/// Percentages of the time spent in each state between two samples.
+#[derive(Clone, Default, Debug)]
+pub struct CpuBreakdown {
+    /// Everything except idle and iowait.
+    pub busy: f32,
+    /// User mode, including niced processes.
+    pub user: f32,
+    /// Kernel mode.
+    pub system: f32,
+    /// Idle while waiting for io.
+    pub iowait: f32,
+    /// Servicing interrupts.
+    pub irq: f32,
+    /// Servicing softirqs.
+    pub softirq: f32,
+    /// Stolen by the hypervisor.
+    pub steal: f32,
+}
+
+impl CpuBreakdown {
+    /// The breakdown of the time that passed between `previous` and `current`.
+    pub fn between(current: &CpuTimes, previous: &CpuTimes) -> Self {
+        let total = current.total().saturating_sub(previous.total());
+        if total == 0 {
+            return CpuBreakdown::default();
+        }
+        let percent = |current: u64, previous: u64| {
+            current.saturating_sub(previous) as f32 / total as f32 * 100_f32
+        };
+        let idle = percent(current.idle, previous.idle);
+        let iowait = percent(current.iowait, previous.iowait);
+        CpuBreakdown {
+            // time spent waiting for io is idle time too
+            busy: 100_f32 - idle - iowait,
+            user: percent(current.user + current.nice, previous.user + previous.nice),
+            system: percent(current.system, previous.system),
+            iowait,
+            irq: percent(current.irq, previous.irq),
+            softirq: percent(current.softirq, previous.softirq),
+            steal: percent(current.steal, previous.steal),
+        }
+    }
+
+    /// A one line description of every state, for labels and tooltips.
+    pub fn summary(&self) -> String {
+        format!(
+            "user {:.1}% | system {:.1}% | iowait {:.1}% | irq {:.1}% | softirq {:.1}% | steal {:.1}%",
+            self.user, self.system, self.iowait, self.irq, self.softirq, self.steal
+        )
+    }
+}
+
+/// Usage of the whole machine and of every core.
+#[derive(Clone, Default, Debug)]
+pub struct CpuUsage {
+    /// The aggregate of all of the cores.
+    pub total: CpuBreakdown,
+    /// One entry per core, in the order of `cpu0..cpuN`.
+    pub cores: Vec<CpuBreakdown>,
+}
+//################################################################
+/// Reads the aggregate cpu line of `/proc/stat` first, followed by the `cpu0..cpuN` lines.
+pub fn read_cpu_times(root: &ProcRoot) -> Result<Vec<CpuTimes>> {
+    let status_content = files::read_to_string(root.file("stat"))?;
+    Ok(status_content
+        .lines()
+        .filter(|line| line.starts_with("cpu"))
+        .map(CpuTimes::parse)
+        .collect())
+}
+//################################################################
+/// Computes the usage since `previous_times` and replaces them with the current times.
+///
+/// Pass an empty vector the first time, the usage is then measured since boot.
+pub fn read_cpu_usage(root: &ProcRoot, previous_times: &mut Vec<CpuTimes>) -> Result<CpuUsage> {
+    let cpu_times = read_cpu_times(root)?;
+    if cpu_times.is_empty() {
+        return Err(Error::parse(root.file("stat"), "no cpu lines"));
+    }
+
+    let mut breakdowns = cpu_times.iter().enumerate().map(|(index, current)| {
+        // a core we have not seen before (first sample or cpu hotplug) is measured since boot
+        let previous = previous_times.get(index).copied().unwrap_or_default();
+        CpuBreakdown::between(current, &previous)
+    });
+    let total = breakdowns.next().unwrap_or_default();
+    let cores = breakdowns.collect();
+
+    *previous_times = cpu_times;
+    Ok(CpuUsage { total, cores })
+}
+//################################################################
+/// Seconds since boot, from `/proc/uptime`.
+pub fn read_uptime(root: &ProcRoot) -> Result<f32> {
+    read_exact_uptime(root).map(|uptime| uptime as f32)
+}
+//################################################################
+// An f32 only counts whole seconds after about 97 days of uptime, too coarse to measure a
+// refresh interval with.
+pub(crate) fn read_exact_uptime(root: &ProcRoot) -> Result<f64> {
+    let uptime_path = root.file("uptime");
+    let uptime_content = files::read_to_string(&uptime_path)?;
+    uptime_content
+        .split_whitespace()
+        .next()
+        .and_then(|value| value.parse::<f64>().ok())
+        .ok_or_else(|| Error::parse(uptime_path, "missing uptime"))
+}
+//################################################################
+// The increase of a counter per second over `seconds`, the difference of two exact uptimes. Two
+// samples within the resolution of the uptime have nothing to measure.
+pub(crate) fn per_second(current: u64, before: u64, seconds: f64) -> f32 {
+    if seconds > 0. {
+        (current.saturating_sub(before) as f64 / seconds) as f32
+    } else {
+        0.
+    }
+}
+//################################################################
+/// The number of online cores, at least 1.
+pub fn online_cpu_count() -> usize {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if count > 0 {
+        count as usize
+    } else {
+        1
+    }
+}
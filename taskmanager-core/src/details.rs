@@ -0,0 +1,148 @@
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use crate::socket::{read_process_sockets, Socket};
+use crate::stat::{read_process_stat, ProcessStat};
+
+/// Everything about one process that the details panel shows.
+///
+/// Most of these files are only readable by the owner of the process (or root), so every
+/// part keeps its own error.
+pub struct ProcessDetails {
+    /// The process id.
+    pub pid: u32,
+    /// The arguments, joined by spaces.
+    pub command_line: Result<String>,
+    /// The environment variables, as `KEY=value`.
+    pub environment: Result<Vec<String>>,
+    /// The current working directory.
+    pub cwd: Result<String>,
+    /// The open file descriptors and what they point to.
+    pub open_files: Result<Vec<(u32, String)>>,
+    /// The open sockets, as `(fd, socket)` pairs.
+    pub sockets: Result<Vec<(u32, Socket)>>,
+    /// A summary of `/proc/<pid>/maps`.
+    pub memory_maps: Result<MapsSummary>,
+    /// The raw `/proc/<pid>/limits` table.
+    pub limits: Result<String>,
+    /// The raw `/proc/<pid>/cgroup` file.
+    pub cgroup: Result<String>,
+    /// The namespaces, as `(name, link)` pairs like `("net", "net:[4026531840]")`.
+    pub namespaces: Result<Vec<(String, String)>>,
+    /// The parsed `/proc/<pid>/stat`, with the priority, nice value and start time.
+    pub stat: Result<ProcessStat>,
+}
+
+/// Sizes of the memory mappings of a process, in kB.
+#[derive(Default)]
+pub struct MapsSummary {
+    /// The number of mappings.
+    pub count: usize,
+    /// The size of every mapping together.
+    pub total_kb: u64,
+    /// Mappings of files.
+    pub file_backed_kb: u64,
+    /// Mappings without a file, like the heap and the stack.
+    pub anonymous_kb: u64,
+    /// Writable mappings.
+    pub writable_kb: u64,
+}
+
+impl ProcessDetails {
+    /// Reads every part of the details. A part that fails does not stop the others.
+    pub fn read(root: &ProcRoot, pid: u32) -> Self {
+        ProcessDetails {
+            pid,
+            command_line: read_command_line(root, pid),
+            environment: read_environment(root, pid),
+            cwd: files::read_link(root.process_file(pid, "cwd"))
+                .map(|path| path.to_string_lossy().to_string()),
+            open_files: read_open_files(root, pid),
+            sockets: read_process_sockets(root, pid),
+            memory_maps: read_memory_maps(root, pid),
+            limits: files::read_to_string(root.process_file(pid, "limits")),
+            cgroup: files::read_to_string(root.process_file(pid, "cgroup")),
+            namespaces: read_namespaces(root, pid),
+            stat: read_process_stat(root, pid),
+        }
+    }
+}
+//################################################################
+pub(crate) fn read_command_line(root: &ProcRoot, pid: u32) -> Result<String> {
+    // the arguments are separated by NUL bytes
+    let command_line = files::read(root.process_file(pid, "cmdline"))?;
+    Ok(command_line
+        .split(|byte| *byte == 0)
+        .filter(|argument| !argument.is_empty())
+        .map(|argument| String::from_utf8_lossy(argument).to_string())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+//################################################################
+fn read_environment(root: &ProcRoot, pid: u32) -> Result<Vec<String>> {
+    let environment = files::read(root.process_file(pid, "environ"))?;
+    Ok(environment
+        .split(|byte| *byte == 0)
+        .filter(|variable| !variable.is_empty())
+        .map(|variable| String::from_utf8_lossy(variable).to_string())
+        .collect())
+}
+//################################################################
+fn read_open_files(root: &ProcRoot, pid: u32) -> Result<Vec<(u32, String)>> {
+    let mut open_files = Vec::new();
+    for entry in files::read_dir(root.process_file(pid, "fd"))?.filter_map(|e| e.ok()) {
+        if let Ok(fd) = entry.file_name().to_string_lossy().parse::<u32>() {
+            let target = match std::fs::read_link(entry.path()) {
+                Ok(target) => target.to_string_lossy().to_string(),
+                Err(error) => error.to_string(),
+            };
+            open_files.push((fd, target));
+        }
+    }
+    open_files.sort_by_key(|(fd, _)| *fd);
+    Ok(open_files)
+}
+//################################################################
+fn read_memory_maps(root: &ProcRoot, pid: u32) -> Result<MapsSummary> {
+    let maps = files::read_to_string(root.process_file(pid, "maps"))?;
+    let mut summary = MapsSummary::default();
+
+    for line in maps.lines() {
+        // address perms offset dev inode [path]
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some((start, end)) = fields.first().and_then(|range| range.split_once('-')) else {
+            continue;
+        };
+        let (Ok(start), Ok(end)) = (u64::from_str_radix(start, 16), u64::from_str_radix(end, 16))
+        else {
+            continue;
+        };
+        let size_kb = end.saturating_sub(start) / 1024;
+
+        summary.count += 1;
+        summary.total_kb += size_kb;
+        // anonymous mappings have no path, or a pseudo path like [heap] or [stack]
+        match fields.get(5) {
+            Some(path) if !path.starts_with('[') => summary.file_backed_kb += size_kb,
+            _ => summary.anonymous_kb += size_kb,
+        }
+        if fields.get(1).is_some_and(|perms| perms.contains('w')) {
+            summary.writable_kb += size_kb;
+        }
+    }
+    Ok(summary)
+}
+//################################################################
+fn read_namespaces(root: &ProcRoot, pid: u32) -> Result<Vec<(String, String)>> {
+    let mut namespaces = Vec::new();
+    for entry in files::read_dir(root.process_file(pid, "ns"))?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let target = match std::fs::read_link(entry.path()) {
+            Ok(target) => target.to_string_lossy().to_string(),
+            Err(error) => error.to_string(),
+        };
+        namespaces.push((name, target));
+    }
+    namespaces.sort();
+    Ok(namespaces)
+}
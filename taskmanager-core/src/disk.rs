@@ -0,0 +1,167 @@
+use crate::cpu::{per_second, read_exact_uptime};
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use serde::Serialize;
+
+// /proc/diskstats always counts in 512 byte sectors, whatever the sector size of the device
+const SECTOR_SIZE: f32 = 512.;
+
+/// One line of `/proc/diskstats`, with the counters since boot.
+#[derive(Clone, Default, Debug)]
+pub struct DiskStats {
+    /// The major device number.
+    pub major: u32,
+    /// The minor device number.
+    pub minor: u32,
+    /// The device name, like `sda` or `nvme0n1p2`.
+    pub name: String,
+    /// Reads completed.
+    pub reads_completed: u64,
+    /// Adjacent reads merged into one.
+    pub reads_merged: u64,
+    /// Sectors read.
+    pub sectors_read: u64,
+    /// Milliseconds spent reading.
+    pub read_time_ms: u64,
+    /// Writes completed.
+    pub writes_completed: u64,
+    /// Adjacent writes merged into one.
+    pub writes_merged: u64,
+    /// Sectors written.
+    pub sectors_written: u64,
+    /// Milliseconds spent writing.
+    pub write_time_ms: u64,
+    /// Requests that are in flight right now.
+    pub in_progress: u64,
+    /// Milliseconds during which the device had requests in flight.
+    pub io_time_ms: u64,
+    /// Milliseconds spent on requests, multiplied by the number of requests in flight.
+    pub weighted_io_time_ms: u64,
+}
+
+impl DiskStats {
+    /// Parses a line of `/proc/diskstats`. Missing counters are read as 0; `None` when the
+    /// line has no device name.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let major = fields.next()?.parse().unwrap_or(0);
+        let minor = fields.next()?.parse().unwrap_or(0);
+        let name = fields.next()?.to_string();
+        let mut values = fields.map(|value| value.parse::<u64>().unwrap_or(0));
+        let mut next_value = || values.next().unwrap_or(0);
+        // newer kernels add discard and flush counters at the end, which are not used
+        Some(DiskStats {
+            major,
+            minor,
+            name,
+            reads_completed: next_value(),
+            reads_merged: next_value(),
+            sectors_read: next_value(),
+            read_time_ms: next_value(),
+            writes_completed: next_value(),
+            writes_merged: next_value(),
+            sectors_written: next_value(),
+            write_time_ms: next_value(),
+            in_progress: next_value(),
+            io_time_ms: next_value(),
+            weighted_io_time_ms: next_value(),
+        })
+    }
+}
+
+/// The counters of every block device at one moment, to measure the next usage against.
+#[derive(Clone, Default, Debug)]
+pub struct DiskSample {
+    /// Seconds since boot when the counters were read.
+    pub uptime: f64,
+    /// Every line of `/proc/diskstats`.
+    pub disks: Vec<DiskStats>,
+}
+
+/// The usage of one block device between two samples.
+#[derive(Clone, Default, Debug, Serialize)]
+pub struct DiskUsage {
+    /// The device name.
+    pub name: String,
+    /// True for partitions of another device in the list, like `sda1` of `sda`.
+    pub is_partition: bool,
+    /// True for loop and ram devices, which are backed by files or memory.
+    pub is_virtual: bool,
+    /// Bytes read per second.
+    pub read_bytes_per_second: f32,
+    /// Bytes written per second.
+    pub write_bytes_per_second: f32,
+    /// Reads completed per second.
+    pub reads_per_second: f32,
+    /// Writes completed per second.
+    pub writes_per_second: f32,
+    /// Percent of the time the device was busy with at least one request.
+    pub utilization: f32,
+    /// The average number of requests in flight, like the `aqu-sz` column of iostat.
+    pub queue_depth: f32,
+}
+//################################################################
+/// Reads every line of `/proc/diskstats`.
+pub fn read_disk_stats(root: &ProcRoot) -> Result<Vec<DiskStats>> {
+    let diskstats = files::read_to_string(root.file("diskstats"))?;
+    Ok(diskstats.lines().filter_map(DiskStats::parse).collect())
+}
+//################################################################
+/// Computes the usage of every device since `previous` and replaces it with the current sample.
+///
+/// Pass a default sample the first time, the usage is then measured since boot.
+pub fn read_disk_usage(root: &ProcRoot, previous: &mut DiskSample) -> Result<Vec<DiskUsage>> {
+    let current = DiskSample {
+        uptime: read_exact_uptime(root)?,
+        disks: read_disk_stats(root)?,
+    };
+    let seconds = current.uptime - previous.uptime;
+
+    let usage = current
+        .disks
+        .iter()
+        .map(|disk| {
+            // a device we have not seen before (first sample or hotplug) is measured since boot
+            let before = previous
+                .disks
+                .iter()
+                .find(|before| before.name == disk.name)
+                .cloned()
+                .unwrap_or_default();
+            let per_second = |current: u64, before: u64| per_second(current, before, seconds);
+            DiskUsage {
+                name: disk.name.clone(),
+                is_partition: current.disks.iter().any(|other| is_partition_of(&disk.name, &other.name)),
+                is_virtual: disk.name.starts_with("loop") || disk.name.starts_with("ram"),
+                read_bytes_per_second: per_second(disk.sectors_read, before.sectors_read) * SECTOR_SIZE,
+                write_bytes_per_second: per_second(disk.sectors_written, before.sectors_written) * SECTOR_SIZE,
+                reads_per_second: per_second(disk.reads_completed, before.reads_completed),
+                writes_per_second: per_second(disk.writes_completed, before.writes_completed),
+                // busy milliseconds per second
+                utilization: (per_second(disk.io_time_ms, before.io_time_ms) / 10.).min(100.),
+                queue_depth: per_second(disk.weighted_io_time_ms, before.weighted_io_time_ms) / 1000.,
+            }
+        })
+        .collect();
+
+    *previous = current;
+    Ok(usage)
+}
+//################################################################
+// sda1 of sda, nvme0n1p2 of nvme0n1 and mmcblk0p1 of mmcblk0, but not loop10 of loop1
+fn is_partition_of(name: &str, device: &str) -> bool {
+    let Some(number) = name.strip_prefix(device) else {
+        return false;
+    };
+    // the kernel puts a p between a device name ending in a digit and the partition number
+    let number = if device.ends_with(|character: char| character.is_ascii_digit()) {
+        match number.strip_prefix('p') {
+            Some(number) => number,
+            None => return false,
+        }
+    } else {
+        number
+    };
+    !number.is_empty() && number.chars().all(|character| character.is_ascii_digit())
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors returned by the collectors.
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be read.
+    Io {
+        /// The file that was read.
+        path: PathBuf,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// A file was read, but its content is not in the expected format.
+    Parse {
+        /// The file that was parsed.
+        path: PathBuf,
+        /// What was wrong with the content.
+        message: String,
+    },
+    /// A system call on a process failed, for example sending a signal.
+    Syscall {
+        /// The name of the system call.
+        call: &'static str,
+        /// The process it was called on.
+        pid: u32,
+        /// The underlying error.
+        source: io::Error,
+    },
+}
+
+/// Result type of the collectors.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn parse(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Error::Parse {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The errno behind the error, if there is one.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Error::Io { source, .. } | Error::Syscall { source, .. } => source.raw_os_error(),
+            Error::Parse { .. } => None,
+        }
+    }
+
+    /// True when the error comes from missing permissions, like reading another user's files.
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            Error::Io { source, .. } | Error::Syscall { source, .. } => {
+                source.kind() == io::ErrorKind::PermissionDenied
+            }
+            Error::Parse { .. } => false,
+        }
+    }
+
+    /// True when the file does not exist, which usually means that the process has exited.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::Io { source, .. } => source.kind() == io::ErrorKind::NotFound,
+            Error::Syscall { source, .. } => source.raw_os_error() == Some(libc::ESRCH),
+            Error::Parse { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(formatter, "{}: {}", path.display(), source),
+            Error::Parse { path, message } => write!(formatter, "{}: {}", path.display(), message),
+            Error::Syscall { call, pid, source } => write!(formatter, "{} on {}: {}", call, pid, source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Syscall { source, .. } => Some(source),
+            Error::Parse { .. } => None,
+        }
+    }
+}
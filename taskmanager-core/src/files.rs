@@ -0,0 +1,31 @@
+// Thin wrappers over std::fs that remember which path failed.
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+pub(crate) fn read_to_string(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(io_error(path))
+}
+
+pub(crate) fn read(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    fs::read(path).map_err(io_error(path))
+}
+
+pub(crate) fn read_link(path: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = path.as_ref();
+    fs::read_link(path).map_err(io_error(path))
+}
+
+pub(crate) fn read_dir(path: impl AsRef<Path>) -> Result<fs::ReadDir> {
+    let path = path.as_ref();
+    fs::read_dir(path).map_err(io_error(path))
+}
@@ -0,0 +1,164 @@
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use serde::Serialize;
+
+// the access mode bits of the open flags, O_RDONLY, O_WRONLY or O_RDWR
+const O_ACCMODE: u32 = 0o3;
+
+/// How a file was opened, from the flags in `/proc/<pid>/fdinfo/<fd>`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum AccessMode {
+    /// Opened with `O_RDONLY`.
+    Read,
+    /// Opened with `O_WRONLY`.
+    Write,
+    /// Opened with `O_RDWR`.
+    ReadWrite,
+}
+
+impl AccessMode {
+    /// Reads the access mode from the octal `flags:` line of an fdinfo file.
+    pub fn parse_fdinfo(fdinfo: &str) -> Option<Self> {
+        let flags = fdinfo.lines().find_map(|line| line.strip_prefix("flags:"))?;
+        match u32::from_str_radix(flags.trim(), 8).ok()? & O_ACCMODE {
+            0 => Some(AccessMode::Read),
+            1 => Some(AccessMode::Write),
+            2 => Some(AccessMode::ReadWrite),
+            _ => None,
+        }
+    }
+
+    /// A short name, like `read/write`.
+    pub fn name(self) -> &'static str {
+        match self {
+            AccessMode::Read => "read",
+            AccessMode::Write => "write",
+            AccessMode::ReadWrite => "read/write",
+        }
+    }
+}
+
+/// How a process holds a file.
+#[derive(Clone, Debug, Serialize)]
+pub enum HandleKind {
+    /// An open file descriptor, with its access mode. The mode is `None` when the fdinfo file
+    /// can not be read.
+    FileDescriptor(u32, Option<AccessMode>),
+    /// One or more memory mappings of the file, like a shared library. The permissions are
+    /// those of every mapping together, like `r-x`.
+    MemoryMap(String),
+}
+
+/// A file that a process has open or mapped.
+#[derive(Clone, Debug, Serialize)]
+pub struct Handle {
+    /// The process id.
+    pub pid: u32,
+    /// The file descriptor or the mapping.
+    pub kind: HandleKind,
+    /// The path, or what the descriptor points to, like `socket:[12345]` or `pipe:[678]`.
+    pub path: String,
+}
+//################################################################
+/// Finds every open file descriptor and memory mapping whose path contains `pattern`, in every
+/// process, ordered by pid.
+///
+/// This reads the `fd` directory and the `maps` file of every process. The processes that the
+/// current user may not inspect are skipped, so run it as root to see everything.
+pub fn find_handles(root: &ProcRoot, pattern: &str) -> Result<Vec<Handle>> {
+    let mut pids: Vec<u32> = files::read_dir(root.path())?
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| entry.file_name().to_string_lossy().parse().ok())
+        .collect();
+    pids.sort_unstable();
+
+    let mut handles = Vec::new();
+    for pid in pids {
+        // the process may have exited, or belong to another user
+        if let Ok(file_handles) = find_file_descriptors(root, pid, pattern) {
+            handles.extend(file_handles);
+        }
+        if let Ok(mapped_handles) = find_memory_maps(root, pid, pattern) {
+            handles.extend(mapped_handles);
+        }
+    }
+    Ok(handles)
+}
+//################################################################
+fn find_file_descriptors(root: &ProcRoot, pid: u32, pattern: &str) -> Result<Vec<Handle>> {
+    let mut fds: Vec<(u32, std::path::PathBuf)> = files::read_dir(root.process_file(pid, "fd"))?
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| Some((entry.file_name().to_string_lossy().parse().ok()?, entry.path())))
+        .collect();
+    fds.sort_by_key(|(fd, _)| *fd);
+
+    let mut handles = Vec::new();
+    for (fd, link) in fds {
+        // the file may have been closed since the directory was read
+        let Ok(target) = std::fs::read_link(link) else {
+            continue;
+        };
+        let path = target.to_string_lossy().to_string();
+        if !path.contains(pattern) {
+            continue;
+        }
+        let access = files::read_to_string(root.process_file(pid, &format!("fdinfo/{}", fd)))
+            .ok()
+            .and_then(|fdinfo| AccessMode::parse_fdinfo(&fdinfo));
+        handles.push(Handle {
+            pid,
+            kind: HandleKind::FileDescriptor(fd, access),
+            path,
+        });
+    }
+    Ok(handles)
+}
+//################################################################
+// A library is usually mapped several times, with different permissions. Those are merged into
+// one handle per path.
+fn find_memory_maps(root: &ProcRoot, pid: u32, pattern: &str) -> Result<Vec<Handle>> {
+    let maps = files::read_to_string(root.process_file(pid, "maps"))?;
+    let mut mapped: Vec<(String, [bool; 3])> = Vec::new();
+
+    for line in maps.lines() {
+        // address perms offset dev inode [path]; the path may contain spaces
+        let mut fields = line.splitn(6, char::is_whitespace);
+        let Some(permissions) = fields.nth(1) else {
+            continue;
+        };
+        let Some(path) = fields.nth(3).map(str::trim) else {
+            continue;
+        };
+        // pseudo paths like [heap] are not files
+        if !path.starts_with('/') || !path.contains(pattern) {
+            continue;
+        }
+        let index = match mapped.iter().position(|(mapped_path, _)| mapped_path == path) {
+            Some(index) => index,
+            None => {
+                mapped.push((path.to_string(), [false; 3]));
+                mapped.len() - 1
+            }
+        };
+        for (allowed, flag) in mapped[index].1.iter_mut().zip(['r', 'w', 'x']) {
+            *allowed |= permissions.contains(flag);
+        }
+    }
+
+    Ok(mapped
+        .into_iter()
+        .map(|(path, allowed)| {
+            let permissions = allowed
+                .iter()
+                .zip(['r', 'w', 'x'])
+                .map(|(allowed, flag)| if *allowed { flag } else { '-' })
+                .collect();
+            Handle {
+                pid,
+                kind: HandleKind::MemoryMap(permissions),
+                path,
+            }
+        })
+        .collect())
+}
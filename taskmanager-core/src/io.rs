@@ -0,0 +1,118 @@
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// The I/O counters of `/proc/<pid>/io`, since the start of the process.
+///
+/// The file is only readable by the owner of the process (or root).
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ProcessIo {
+    /// Bytes read by system calls, including the ones served from the page cache.
+    pub rchar: u64,
+    /// Bytes written by system calls.
+    pub wchar: u64,
+    /// Read system calls.
+    pub syscr: u64,
+    /// Write system calls.
+    pub syscw: u64,
+    /// Bytes that were fetched from storage.
+    pub read_bytes: u64,
+    /// Bytes that were sent to storage.
+    pub write_bytes: u64,
+    /// Bytes of `write_bytes` that were never written, because the file was truncated.
+    pub cancelled_write_bytes: u64,
+}
+//################################################################
+/// Reads `/proc/<pid>/io`. Missing keys are read as 0.
+pub fn read_process_io(root: &ProcRoot, pid: u32) -> Result<ProcessIo> {
+    let io_content = files::read_to_string(root.process_file(pid, "io"))?;
+
+    let mut io = ProcessIo::default();
+    for line in io_content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().parse::<u64>().unwrap_or(0);
+        match key {
+            "rchar" => io.rchar = value,
+            "wchar" => io.wchar = value,
+            "syscr" => io.syscr = value,
+            "syscw" => io.syscw = value,
+            "read_bytes" => io.read_bytes = value,
+            "write_bytes" => io.write_bytes = value,
+            "cancelled_write_bytes" => io.cancelled_write_bytes = value,
+            _ => (),
+        }
+    }
+    Ok(io)
+}
+//################################################################
+struct IoSample {
+    start_time: u64,
+    read_bytes: u64,
+    write_bytes: u64,
+}
+
+/// Remembers the storage I/O of every process from the previous collector pass, so the read and
+/// write rates can be computed over the refresh interval, like [`crate::CpuTracker`] does for
+/// the cpu usage.
+#[derive(Default)]
+pub struct IoTracker {
+    elapsed_seconds: f32,
+    last_pass: Option<Instant>,
+    previous_samples: HashMap<u32, IoSample>,
+    current_samples: HashMap<u32, IoSample>,
+}
+
+impl IoTracker {
+    /// A tracker without any previous samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a pass over the processes. Call it before the first [`IoTracker::process_rates`].
+    pub fn begin_pass(&mut self) {
+        let now = Instant::now();
+        self.elapsed_seconds = match self.last_pass {
+            Some(last_pass) => now.duration_since(last_pass).as_secs_f32(),
+            None => 0.,
+        };
+        self.last_pass = Some(now);
+    }
+
+    /// Ends a pass. The samples of this pass become the previous ones for the next pass.
+    pub fn end_pass(&mut self) {
+        self.previous_samples = std::mem::take(&mut self.current_samples);
+    }
+
+    /// The bytes read from and written to storage per second since the previous pass.
+    ///
+    /// `start_time` is the start of the process in clock ticks, which tells a reused pid apart,
+    /// and `elapsed_seconds` its age, for the average over its lifetime on the first pass.
+    pub fn process_rates(&mut self, pid: u32, start_time: u64, elapsed_seconds: f32, io: &ProcessIo) -> (f32, f32) {
+        let (read_bytes, write_bytes, seconds) = match self.previous_samples.get(&pid) {
+            Some(previous) if previous.start_time == start_time && self.elapsed_seconds > 0. => (
+                io.read_bytes.saturating_sub(previous.read_bytes),
+                io.write_bytes.saturating_sub(previous.write_bytes),
+                self.elapsed_seconds,
+            ),
+            _ => (io.read_bytes, io.write_bytes, elapsed_seconds),
+        };
+
+        self.current_samples.insert(
+            pid,
+            IoSample {
+                start_time,
+                read_bytes: io.read_bytes,
+                write_bytes: io.write_bytes,
+            },
+        );
+        if seconds > 0. {
+            (read_bytes as f32 / seconds, write_bytes as f32 / seconds)
+        } else {
+            (0., 0.)
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Collectors behind the task manager: processes, cpu, memory, disk and network usage and the files
+//! and sockets that processes hold, read from `/proc`.
+//!
+//! Every reader takes a [`ProcRoot`], so the collectors can also read a fixture directory or the
+//! `/proc` of the host bind-mounted into a container.
+//!
+//! [`Sampler`] is the entry point for most uses. The parsing functions it is built on are
+//! public too, for tools that only need one part of the data.
+#![warn(missing_docs)]
+
+mod cpu;
+mod details;
+mod disk;
+mod error;
+mod files;
+mod handle;
+mod io;
+mod memory;
+mod net;
+mod priority;
+mod process;
+mod root;
+mod sampler;
+mod signal;
+mod socket;
+mod stat;
+mod thread;
+mod tree;
+
+pub use cpu::{online_cpu_count, read_cpu_times, read_cpu_usage, read_uptime, CpuBreakdown, CpuTimes, CpuUsage};
+pub use details::{MapsSummary, ProcessDetails};
+pub use disk::{read_disk_stats, read_disk_usage, DiskSample, DiskStats, DiskUsage};
+pub use error::{Error, Result};
+pub use handle::{find_handles, AccessMode, Handle, HandleKind};
+pub use io::{read_process_io, IoTracker, ProcessIo};
+pub use memory::{kb_to_gb, read_memory_usage, MemoryInfo};
+pub use net::{read_network_stats, read_network_usage, InterfaceStats, InterfaceUsage, NetworkSample};
+pub use priority::{
+    format_cpu_list, parse_cpu_list, read_io_priority, set_cpu_affinity, set_io_priority, set_nice, IoPriority,
+    IoPriorityClass,
+};
+pub use process::{
+    count_open_files, get_children_processes, get_process_data, get_process_file_path,
+    read_process_cgroup, read_process_info, CpuTracker, ProcInfo, ProcessFields, ProcessStatus, UserCache, ACCESS_DENIED, NOT_AVAILABLE, NOT_FOUND,
+};
+pub use root::ProcRoot;
+pub use sampler::{Sampler, Snapshot};
+pub use signal::{describe_syscall_error, send_signal, ProcessSignal};
+pub use socket::{
+    read_connections, read_process_sockets, read_sockets, socket_inode, Connection, Socket, SocketProtocol,
+};
+pub use stat::{read_process_stat, ProcessStat};
+pub use thread::{read_threads, ThreadInfo};
+pub use tree::ProcessTree;
@@ -0,0 +1,105 @@
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use std::collections::HashMap;
+
+/// Values from `/proc/meminfo`, in kB.
+#[derive(Clone, Default, Debug)]
+pub struct MemoryInfo {
+    /// MemTotal.
+    pub total: u64,
+    /// MemFree.
+    pub free: u64,
+    /// MemAvailable, or an estimate of it on kernels that do not report it.
+    pub available: u64,
+    /// Buffers.
+    pub buffers: u64,
+    /// Cached.
+    pub cached: u64,
+    /// Shmem.
+    pub shared: u64,
+    /// Slab.
+    pub slab: u64,
+    /// Dirty.
+    pub dirty: u64,
+    /// Writeback.
+    pub writeback: u64,
+    /// SwapTotal.
+    pub swap_total: u64,
+    /// SwapFree.
+    pub swap_free: u64,
+    /// HugePages_Total, in pages.
+    pub hugepages_total: u64,
+    /// HugePages_Free, in pages.
+    pub hugepages_free: u64,
+    /// Hugepagesize.
+    pub hugepage_size: u64,
+}
+
+impl MemoryInfo {
+    /// Parses the content of `/proc/meminfo` by key. Missing keys are read as 0.
+    pub fn parse(meminfo_content: &str) -> Self {
+        // every line looks like "Key:   value kB", the order differs between kernels
+        let values: HashMap<&str, u64> = meminfo_content
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                let value = value.split_whitespace().next()?.parse::<u64>().ok()?;
+                Some((key.trim(), value))
+            })
+            .collect();
+        let value = |key: &str| values.get(key).copied().unwrap_or(0);
+
+        let mut memory_info = MemoryInfo {
+            total: value("MemTotal"),
+            free: value("MemFree"),
+            available: value("MemAvailable"),
+            buffers: value("Buffers"),
+            cached: value("Cached"),
+            shared: value("Shmem"),
+            slab: value("Slab"),
+            dirty: value("Dirty"),
+            writeback: value("Writeback"),
+            swap_total: value("SwapTotal"),
+            swap_free: value("SwapFree"),
+            hugepages_total: value("HugePages_Total"),
+            hugepages_free: value("HugePages_Free"),
+            hugepage_size: value("Hugepagesize"),
+        };
+        // kernels older than 3.14 do not report MemAvailable
+        if !values.contains_key("MemAvailable") {
+            memory_info.available = memory_info.free + memory_info.buffers + memory_info.cached;
+        }
+        memory_info
+    }
+
+    /// Memory that can not be made available without swapping.
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Memory that is neither free nor one of the kernel caches.
+    pub fn used_by_applications(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cached)
+            .saturating_sub(self.slab)
+    }
+
+    /// Swap in use.
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+}
+
+/// Converts kB to GB.
+pub fn kb_to_gb(kb: u64) -> f32 {
+    kb as f32 / 1_048_576.0
+}
+//################################################################
+/// Reads `/proc/meminfo`.
+pub fn read_memory_usage(root: &ProcRoot) -> Result<MemoryInfo> {
+    let meminfo_content = files::read_to_string(root.file("meminfo"))?;
+    Ok(MemoryInfo::parse(&meminfo_content))
+}
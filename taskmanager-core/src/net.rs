@@ -0,0 +1,161 @@
+use crate::cpu::{per_second, read_exact_uptime};
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use serde::Serialize;
+
+// name prefixes of bridges, tunnels and the host ends of container and vm interfaces
+const VIRTUAL_INTERFACE_PREFIXES: [&str; 14] = [
+    "veth", "docker", "br-", "virbr", "vnet", "tun", "tap", "wg", "cni", "flannel", "cali", "tailscale", "dummy", "ifb",
+];
+
+/// The counters of one interface in `/proc/net/dev`, since boot.
+#[derive(Clone, Default, Debug)]
+pub struct InterfaceStats {
+    /// The interface name, like `eth0`.
+    pub name: String,
+    /// Bytes received.
+    pub rx_bytes: u64,
+    /// Packets received.
+    pub rx_packets: u64,
+    /// Receive errors.
+    pub rx_errors: u64,
+    /// Received packets that were dropped.
+    pub rx_dropped: u64,
+    /// Bytes sent.
+    pub tx_bytes: u64,
+    /// Packets sent.
+    pub tx_packets: u64,
+    /// Transmit errors.
+    pub tx_errors: u64,
+    /// Packets that were dropped instead of sent.
+    pub tx_dropped: u64,
+}
+
+impl InterfaceStats {
+    /// Parses an interface line of `/proc/net/dev`. `None` for the two header lines.
+    pub fn parse(line: &str) -> Option<Self> {
+        let (name, values) = line.split_once(':')?;
+        let values: Vec<u64> = values
+            .split_whitespace()
+            .map(|value| value.parse::<u64>().unwrap_or(0))
+            .collect();
+        let value = |index: usize| values.get(index).copied().unwrap_or(0);
+        // bytes packets errs drop fifo frame compressed multicast, then the same for transmit
+        Some(InterfaceStats {
+            name: name.trim().to_string(),
+            rx_bytes: value(0),
+            rx_packets: value(1),
+            rx_errors: value(2),
+            rx_dropped: value(3),
+            tx_bytes: value(8),
+            tx_packets: value(9),
+            tx_errors: value(10),
+            tx_dropped: value(11),
+        })
+    }
+
+    /// True for the loopback interface.
+    pub fn is_loopback(&self) -> bool {
+        self.name == "lo"
+    }
+
+    /// True for bridges, tunnels and the interfaces of containers and virtual machines, guessed
+    /// from the name.
+    pub fn is_virtual(&self) -> bool {
+        VIRTUAL_INTERFACE_PREFIXES.iter().any(|prefix| self.name.starts_with(prefix))
+    }
+}
+
+/// The counters of every interface at one moment, to measure the next usage against.
+#[derive(Clone, Default, Debug)]
+pub struct NetworkSample {
+    /// Seconds since boot when the counters were read.
+    pub uptime: f64,
+    /// Every interface of `/proc/net/dev`.
+    pub interfaces: Vec<InterfaceStats>,
+}
+
+/// The throughput of one interface between two samples.
+#[derive(Clone, Default, Debug, Serialize)]
+pub struct InterfaceUsage {
+    /// The interface name.
+    pub name: String,
+    /// True for the loopback interface.
+    pub is_loopback: bool,
+    /// See [`InterfaceStats::is_virtual`].
+    pub is_virtual: bool,
+    /// Bytes received per second.
+    pub rx_bytes_per_second: f32,
+    /// Bytes sent per second.
+    pub tx_bytes_per_second: f32,
+    /// Packets received per second.
+    pub rx_packets_per_second: f32,
+    /// Packets sent per second.
+    pub tx_packets_per_second: f32,
+    /// Receive and transmit errors per second.
+    pub errors_per_second: f32,
+    /// Dropped packets per second, in both directions.
+    pub dropped_per_second: f32,
+    /// Receive and transmit errors since boot.
+    pub errors: u64,
+    /// Dropped packets since boot, in both directions.
+    pub dropped: u64,
+}
+//################################################################
+/// Reads every interface of `/proc/net/dev`.
+///
+/// The file lists the interfaces of the network namespace of the reading process.
+pub fn read_network_stats(root: &ProcRoot) -> Result<Vec<InterfaceStats>> {
+    let net_dev = files::read_to_string(root.file("net/dev"))?;
+    Ok(net_dev.lines().filter_map(InterfaceStats::parse).collect())
+}
+//################################################################
+/// Computes the throughput of every interface since `previous` and replaces it with the current
+/// sample.
+///
+/// Pass a default sample the first time, the throughput is then measured since boot.
+pub fn read_network_usage(root: &ProcRoot, previous: &mut NetworkSample) -> Result<Vec<InterfaceUsage>> {
+    let current = NetworkSample {
+        uptime: read_exact_uptime(root)?,
+        interfaces: read_network_stats(root)?,
+    };
+    let seconds = current.uptime - previous.uptime;
+
+    let usage = current
+        .interfaces
+        .iter()
+        .map(|interface| {
+            // an interface we have not seen before is measured since boot
+            let before = previous
+                .interfaces
+                .iter()
+                .find(|before| before.name == interface.name)
+                .cloned()
+                .unwrap_or_default();
+            let per_second = |current: u64, before: u64| per_second(current, before, seconds);
+            InterfaceUsage {
+                name: interface.name.clone(),
+                is_loopback: interface.is_loopback(),
+                is_virtual: interface.is_virtual(),
+                rx_bytes_per_second: per_second(interface.rx_bytes, before.rx_bytes),
+                tx_bytes_per_second: per_second(interface.tx_bytes, before.tx_bytes),
+                rx_packets_per_second: per_second(interface.rx_packets, before.rx_packets),
+                tx_packets_per_second: per_second(interface.tx_packets, before.tx_packets),
+                errors_per_second: per_second(
+                    interface.rx_errors + interface.tx_errors,
+                    before.rx_errors + before.tx_errors,
+                ),
+                dropped_per_second: per_second(
+                    interface.rx_dropped + interface.tx_dropped,
+                    before.rx_dropped + before.tx_dropped,
+                ),
+                errors: interface.rx_errors + interface.tx_errors,
+                dropped: interface.rx_dropped + interface.tx_dropped,
+            }
+        })
+        .collect();
+
+    *previous = current;
+    Ok(usage)
+}
@@ -0,0 +1,246 @@
+use crate::error::{Error, Result};
+use crate::files;
+use crate::root::ProcRoot;
+use serde::Serialize;
+
+// from linux/ioprio.h, which libc does not have
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+const IOPRIO_LEVEL_MASK: i32 = (1 << IOPRIO_CLASS_SHIFT) - 1;
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+// the largest NR_CPUS the kernel can be built with
+const MAX_CPUS: usize = 8192;
+
+/// The I/O scheduling classes of `ioprio_set`, like the `-c` option of `ionice`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum IoPriorityClass {
+    /// No class was set, the process gets best effort at a level derived from its nice value.
+    None,
+    /// Served before everyone else. Setting it needs root.
+    Realtime,
+    /// The default class, served by level.
+    BestEffort,
+    /// Served only when no one else uses the disk.
+    Idle,
+}
+
+impl IoPriorityClass {
+    /// Every class, in the order they are offered to the user.
+    pub const ALL: [IoPriorityClass; 4] = [
+        IoPriorityClass::None,
+        IoPriorityClass::Realtime,
+        IoPriorityClass::BestEffort,
+        IoPriorityClass::Idle,
+    ];
+
+    /// The name `ionice` prints, like `best-effort`.
+    pub fn name(self) -> &'static str {
+        match self {
+            IoPriorityClass::None => "none",
+            IoPriorityClass::Realtime => "realtime",
+            IoPriorityClass::BestEffort => "best-effort",
+            IoPriorityClass::Idle => "idle",
+        }
+    }
+
+    /// True for the classes that have levels.
+    pub fn has_levels(self) -> bool {
+        matches!(self, IoPriorityClass::Realtime | IoPriorityClass::BestEffort)
+    }
+}
+
+/// The I/O priority of a process.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub struct IoPriority {
+    /// The scheduling class.
+    pub class: IoPriorityClass,
+    /// From 0, the highest, to 7. Only used by the realtime and best effort classes.
+    pub level: u8,
+}
+
+impl Default for IoPriority {
+    fn default() -> Self {
+        IoPriority {
+            class: IoPriorityClass::None,
+            level: 4,
+        }
+    }
+}
+
+impl IoPriority {
+    /// Splits the value returned by `ioprio_get`.
+    pub fn from_raw(raw: i32) -> Self {
+        let class = match raw >> IOPRIO_CLASS_SHIFT {
+            1 => IoPriorityClass::Realtime,
+            2 => IoPriorityClass::BestEffort,
+            3 => IoPriorityClass::Idle,
+            _ => IoPriorityClass::None,
+        };
+        IoPriority {
+            class,
+            level: (raw & IOPRIO_LEVEL_MASK).clamp(0, 7) as u8,
+        }
+    }
+
+    /// The value for `ioprio_set`.
+    pub fn as_raw(self) -> i32 {
+        let class = match self.class {
+            IoPriorityClass::None => 0,
+            IoPriorityClass::Realtime => 1,
+            IoPriorityClass::BestEffort => 2,
+            IoPriorityClass::Idle => 3,
+        };
+        let level = if self.class.has_levels() { i32::from(self.level.min(7)) } else { 0 };
+        (class << IOPRIO_CLASS_SHIFT) | level
+    }
+
+    /// The class, followed by the level for the classes that have one, like `best-effort 4`.
+    pub fn label(self) -> String {
+        if self.class.has_levels() {
+            format!("{} {}", self.class.name(), self.level)
+        } else {
+            self.class.name().to_string()
+        }
+    }
+}
+//################################################################
+/// Parses a cpu list like `0-3,8,10-11`, the format of `Cpus_allowed_list` in
+/// `/proc/<pid>/status`. Parts that are not numbers are skipped, and so are cpus from 8192 on,
+/// more than the kernel supports.
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                if let (Ok(first), Ok(last)) = (first.parse::<usize>(), last.parse::<usize>()) {
+                    cpus.extend(first..=last.min(MAX_CPUS - 1));
+                }
+            }
+            None => cpus.extend(part.parse::<usize>().ok().filter(|cpu| *cpu < MAX_CPUS)),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    cpus
+}
+//################################################################
+/// Writes sorted cpus as a list like `0-3,8`, the inverse of [`parse_cpu_list`].
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == cpu => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+//################################################################
+/// Reads the I/O priority of a process with `ioprio_get`.
+///
+/// Like the other system calls here, this works on the pids of the running system, not on
+/// those of another procfs root.
+pub fn read_io_priority(pid: u32) -> Result<IoPriority> {
+    let syscall_error = |source| Error::Syscall {
+        call: "ioprio_get",
+        pid,
+        source,
+    };
+    let raw_pid = positive_pid(pid).ok_or_else(|| syscall_error(std::io::Error::from_raw_os_error(libc::ESRCH)))?;
+    let raw = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, raw_pid) };
+    if raw == -1 {
+        return Err(syscall_error(std::io::Error::last_os_error()));
+    }
+    Ok(IoPriority::from_raw(raw as i32))
+}
+//################################################################
+/// Sets the nice value of every thread of a process with `setpriority`, like `renice`.
+///
+/// Lowering the value needs `CAP_SYS_NICE`; the processes of other users need it too. Like the
+/// other setters, this fails for a root other than the local `/proc`.
+pub fn set_nice(root: &ProcRoot, pid: u32, nice: i32) -> Result<()> {
+    for_each_thread(root, pid, "setpriority", |tid| unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice.clamp(-20, 19)) as libc::c_long
+    })
+}
+//################################################################
+/// Sets the I/O priority of every thread of a process with `ioprio_set`, like `ionice`.
+pub fn set_io_priority(root: &ProcRoot, pid: u32, priority: IoPriority) -> Result<()> {
+    for_each_thread(root, pid, "ioprio_set", |tid| unsafe {
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, priority.as_raw())
+    })
+}
+//################################################################
+/// Sets the cpus every thread of a process may run on with `sched_setaffinity`, like
+/// `taskset -a`.
+///
+/// The set holds the cpus below 1024; a higher one fails with `EINVAL` before anything changed.
+pub fn set_cpu_affinity(root: &ProcRoot, pid: u32, cpus: &[usize]) -> Result<()> {
+    let mut cpu_set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let set_size = 8 * std::mem::size_of::<libc::cpu_set_t>();
+    for &cpu in cpus {
+        // CPU_SET does not check the index
+        if cpu >= set_size {
+            return Err(Error::Syscall {
+                call: "sched_setaffinity",
+                pid,
+                source: std::io::Error::from_raw_os_error(libc::EINVAL),
+            });
+        }
+        unsafe { libc::CPU_SET(cpu, &mut cpu_set) };
+    }
+    for_each_thread(root, pid, "sched_setaffinity", |tid| unsafe {
+        libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &cpu_set) as libc::c_long
+    })
+}
+//################################################################
+// pid 0 would be the calling process, and a negative one a process group
+fn positive_pid(pid: u32) -> Option<libc::pid_t> {
+    libc::pid_t::try_from(pid).ok().filter(|raw_pid| *raw_pid > 0)
+}
+
+// The nice value, the I/O priority and the affinity belong to a thread, so they are changed on
+// every thread in `/proc/<pid>/task`. `apply` returns -1 on failure, like the system calls.
+fn for_each_thread(
+    root: &ProcRoot,
+    pid: u32,
+    call: &'static str,
+    apply: impl Fn(libc::pid_t) -> libc::c_long,
+) -> Result<()> {
+    let syscall_error = |source| Error::Syscall { call, pid, source };
+    // the pids of another root, like a container or a fixture, would name unrelated processes here
+    if !root.is_local() {
+        return Err(syscall_error(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "the procfs root is not the local /proc",
+        )));
+    }
+    let raw_pid = positive_pid(pid).ok_or_else(|| syscall_error(std::io::Error::from_raw_os_error(libc::ESRCH)))?;
+
+    let mut tids: Vec<libc::pid_t> = match files::read_dir(root.process_file(pid, "task")) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter_map(|entry| entry.file_name().to_string_lossy().parse().ok())
+            .filter(|tid| *tid != raw_pid)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    // the main thread goes first, so a process we may not change fails before anything changed
+    tids.sort_unstable();
+    tids.insert(0, raw_pid);
+
+    for tid in tids {
+        if apply(tid) == -1 {
+            let error = std::io::Error::last_os_error();
+            // a thread that exited in the meantime is not an error
+            if tid != raw_pid && error.raw_os_error() == Some(libc::ESRCH) {
+                continue;
+            }
+            return Err(syscall_error(error));
+        }
+    }
+    Ok(())
+}
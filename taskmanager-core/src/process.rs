@@ -0,0 +1,521 @@
+use crate::cpu::read_uptime;
+use crate::details::read_command_line;
+use crate::error::Result;
+use crate::files;
+use crate::io::{read_process_io, IoTracker};
+use crate::priority::{parse_cpu_list, read_io_priority, IoPriority};
+use crate::root::ProcRoot;
+use crate::stat::{read_process_stat, ProcessStat};
+use crate::thread::ThreadInfo;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Shown instead of a value that the current user is not allowed to read.
+pub const ACCESS_DENIED: &str = "Access denied";
+/// Shown instead of the path of processes without an executable, like kernel threads.
+pub const NOT_FOUND: &str = "Not found!";
+/// Shown instead of a value that could not be read at all.
+pub const NOT_AVAILABLE: &str = "N/A";
+
+/// One process, as shown in a row of the process list.
+#[derive(Clone, Serialize, Debug)]
+pub struct ProcInfo {
+    /// The command name, from `/proc/<pid>/status`.
+    pub name: String,
+    /// The name of the real user, or the uid if it has no name.
+    pub user: String,
+    /// The process id.
+    pub pid: u32,
+    /// The one letter state, like `R` or `S`.
+    pub status: String,
+    /// Percent of one core over the last refresh interval.
+    pub cpu: f32,
+    /// Resident memory, in MB.
+    pub memory_used: f32,
+    /// The executable, or one of [`ACCESS_DENIED`] and [`NOT_FOUND`].
+    pub path: String,
+    /// The pid of the parent, 0 for the roots.
+    pub parent_pid: u32,
+    /// The number of threads.
+    pub threads: i64,
+    /// Every thread, when the sampler collects them, see [`crate::Sampler::set_collect_threads`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub thread_list: Vec<ThreadInfo>,
+    /// The nice value, from -20 to 19.
+    pub nice: i64,
+    /// The kernel priority.
+    pub priority: i64,
+    /// The cpu the process last ran on.
+    pub processor: i32,
+    /// Seconds after boot.
+    pub start_time: f32,
+    /// Seconds since the process started.
+    pub elapsed_time: f32,
+    /// Seconds spent on a cpu, in user and kernel mode.
+    pub cpu_time: f32,
+    /// Virtual memory, in MB.
+    pub virtual_memory: f32,
+    /// Resident memory that can be shared with other processes, file mappings and shared
+    /// memory, in MB.
+    pub shared_memory: f32,
+    /// Memory that is swapped out, in MB.
+    pub swap: f32,
+    /// The arguments, joined by spaces. Empty for kernel threads and zombies.
+    pub command_line: String,
+    /// The controlling terminal, like `pts/3`, or empty.
+    pub tty: String,
+    /// The session id.
+    pub session: i32,
+    /// The cgroup, from the last line of `/proc/<pid>/cgroup`, or empty.
+    pub cgroup: String,
+    /// Bytes per second read from storage since the previous pass. `None` when `/proc/<pid>/io`
+    /// can not be read, which needs the permissions of the owner.
+    pub io_read_rate: Option<f32>,
+    /// Bytes per second written to storage since the previous pass, like `io_read_rate`.
+    pub io_write_rate: Option<f32>,
+    /// The number of open file descriptors. `None` when `/proc/<pid>/fd` can not be read.
+    pub open_files: Option<usize>,
+    /// Context switches because the process waited, for example for I/O.
+    pub voluntary_context_switches: u64,
+    /// Context switches because the scheduler preempted the process.
+    pub involuntary_context_switches: u64,
+    /// The I/O priority. `None` when it can not be read, or the root is not the local `/proc`.
+    pub io_priority: Option<IoPriority>,
+    /// The cpus the process may run on. Empty when they can not be read.
+    pub cpu_affinity: Vec<usize>,
+}
+
+/// The values of [`ProcInfo`] that need another file or a system call per process, on top of
+/// `status` and `stat`. The ones that are off are left empty.
+///
+/// The default collects all of them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProcessFields {
+    /// [`ProcInfo::command_line`], from `/proc/<pid>/cmdline`.
+    pub command_line: bool,
+    /// [`ProcInfo::cgroup`], from `/proc/<pid>/cgroup`.
+    pub cgroup: bool,
+    /// [`ProcInfo::io_read_rate`] and [`ProcInfo::io_write_rate`], from `/proc/<pid>/io`.
+    pub io_rates: bool,
+    /// [`ProcInfo::open_files`], which lists `/proc/<pid>/fd`.
+    pub open_files: bool,
+    /// [`ProcInfo::io_priority`], with the `ioprio_get` system call.
+    pub io_priority: bool,
+}
+
+impl ProcessFields {
+    /// Every optional value.
+    pub const ALL: ProcessFields = ProcessFields {
+        command_line: true,
+        cgroup: true,
+        io_rates: true,
+        open_files: true,
+        io_priority: true,
+    };
+    /// Only what `status` and `stat` have.
+    pub const NONE: ProcessFields = ProcessFields {
+        command_line: false,
+        cgroup: false,
+        io_rates: false,
+        open_files: false,
+        io_priority: false,
+    };
+}
+
+impl Default for ProcessFields {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// The fields of `/proc/<pid>/status` that the process list uses.
+#[derive(Debug)]
+pub struct ProcessStatus {
+    /// The process id.
+    pub pid: u32,
+    /// The command name.
+    pub name: String,
+    /// The one letter state.
+    pub status: String,
+    /// Resident memory, in kB.
+    pub memory_used: u32,
+    /// The pid of the parent.
+    pub parent_pid: u32,
+    /// The real user id.
+    pub uid: Option<u32>,
+    /// Virtual memory, in kB.
+    pub virtual_memory: u64,
+    /// Resident file mappings and shared memory, in kB.
+    pub shared_memory: u64,
+    /// Swapped out memory, in kB.
+    pub swap: u64,
+    /// Voluntary context switches.
+    pub voluntary_context_switches: u64,
+    /// Involuntary context switches.
+    pub involuntary_context_switches: u64,
+    /// The cpus the process may run on, from `Cpus_allowed_list`.
+    pub cpus_allowed: Vec<usize>,
+}
+//################################################################
+/// Reads `/proc/<pid>/status`.
+pub fn read_process_info(root: &ProcRoot, pid: u32) -> Result<ProcessStatus> {
+    let status_content = files::read_to_string(root.process_file(pid, "status"))?;
+
+    let mut name = String::new();
+    let mut status = String::new();
+    let mut memory_used: u32 = 0;
+    let mut parent_pid: u32 = 0;
+    let mut uid: Option<u32> = None;
+    let mut virtual_memory: u64 = 0;
+    let mut shared_memory: u64 = 0;
+    let mut swap: u64 = 0;
+    let mut voluntary_context_switches: u64 = 0;
+    let mut involuntary_context_switches: u64 = 0;
+    let mut cpus_allowed = Vec::new();
+
+    for line in status_content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let first_value = value.split_whitespace().next().unwrap_or("");
+
+        match key {
+            // the name can contain spaces, so it is the whole value
+            "Name" => name = value.trim().to_string(),
+            "State" => status = first_value.to_string(),
+            "VmRSS" => {
+                if let Ok(value) = first_value.parse::<u32>() {
+                    memory_used = value;
+                }
+            }
+            "PPid" => {
+                if let Ok(value) = first_value.parse::<u32>() {
+                    parent_pid = value;
+                }
+            }
+            // the first of the four uids is the real one
+            "Uid" => uid = first_value.parse::<u32>().ok(),
+            "VmSize" => virtual_memory = first_value.parse().unwrap_or(0),
+            // like the SHR column of top
+            "RssFile" | "RssShmem" => shared_memory += first_value.parse().unwrap_or(0),
+            "VmSwap" => swap = first_value.parse().unwrap_or(0),
+            "voluntary_ctxt_switches" => voluntary_context_switches = first_value.parse().unwrap_or(0),
+            "nonvoluntary_ctxt_switches" => involuntary_context_switches = first_value.parse().unwrap_or(0),
+            "Cpus_allowed_list" => cpus_allowed = parse_cpu_list(value),
+            _ => (),
+        }
+    }
+
+    Ok(ProcessStatus {
+        pid,
+        name,
+        status,
+        memory_used,
+        parent_pid,
+        uid,
+        virtual_memory,
+        shared_memory,
+        swap,
+        voluntary_context_switches,
+        involuntary_context_switches,
+        cpus_allowed,
+    })
+}
+//################################################################
+/// The executable of a process, from the `/proc/<pid>/exe` link.
+pub fn get_process_file_path(root: &ProcRoot, pid: u32) -> Result<String> {
+    let path = files::read_link(root.process_file(pid, "exe"))?;
+    Ok(path.to_string_lossy().to_string())
+}
+//################################################################
+/// The direct children of a process, from `/proc/<pid>/task/<pid>/children`.
+///
+/// Only the children of the main thread are listed there; [`ProcessTree`](crate::ProcessTree)
+/// has all of them, without reading a file per process.
+pub fn get_children_processes(root: &ProcRoot, pid: u32) -> Result<Vec<u32>> {
+    let path = root.process_file(pid, &format!("task/{}/children", pid));
+    let children_processes = files::read_to_string(path)?;
+
+    Ok(children_processes
+        .split_whitespace()
+        .filter_map(|child| child.parse::<u32>().ok())
+        .collect())
+}
+//################################################################
+/// The cgroup of a process. On cgroup v2 there is a single `0::<path>` line; with v1 the
+/// last hierarchy is shown.
+pub fn read_process_cgroup(root: &ProcRoot, pid: u32) -> Result<String> {
+    let cgroup = files::read_to_string(root.process_file(pid, "cgroup"))?;
+    // hierarchy-ID:controllers:path
+    let path = cgroup
+        .lines()
+        .rev()
+        .find_map(|line| line.splitn(3, ':').nth(2))
+        .unwrap_or("");
+    Ok(path.to_string())
+}
+//################################################################
+/// The number of open file descriptors of a process.
+pub fn count_open_files(root: &ProcRoot, pid: u32) -> Result<usize> {
+    Ok(files::read_dir(root.process_file(pid, "fd"))?.count())
+}
+//################################################################
+/// Resolves user ids to names through a passwd file, `/etc/passwd` by default.
+///
+/// The file is read again only when an unknown uid shows up.
+pub struct UserCache {
+    passwd: Option<PathBuf>,
+    names: HashMap<u32, String>,
+    unknown_uids: HashSet<u32>,
+}
+
+impl Default for UserCache {
+    fn default() -> Self {
+        Self::new(Some(Path::new("/etc/passwd")))
+    }
+}
+
+impl UserCache {
+    /// A cache that reads `passwd`. Without a file every uid is shown as a number.
+    pub fn new(passwd: Option<&Path>) -> Self {
+        Self {
+            passwd: passwd.map(Path::to_path_buf),
+            names: HashMap::new(),
+            unknown_uids: HashSet::new(),
+        }
+    }
+
+    fn load_passwd(&mut self) -> Result<()> {
+        let Some(passwd_path) = &self.passwd else {
+            return Ok(());
+        };
+        let passwd = files::read_to_string(passwd_path)?;
+        for line in passwd.lines() {
+            // name:password:uid:gid:gecos:home:shell
+            let mut fields = line.split(':');
+            let (Some(name), Some(uid)) = (fields.next(), fields.nth(1)) else {
+                continue;
+            };
+            if let Ok(uid) = uid.parse::<u32>() {
+                self.names.insert(uid, name.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The name of the user, or the uid as a string if it has no entry in the passwd file.
+    pub fn user_name(&mut self, uid: u32) -> String {
+        if !self.names.contains_key(&uid) && !self.unknown_uids.contains(&uid) {
+            // without a passwd file every uid is shown as a number
+            let _ = self.load_passwd();
+            if !self.names.contains_key(&uid) {
+                self.unknown_uids.insert(uid);
+            }
+        }
+        match self.names.get(&uid) {
+            Some(name) => name.clone(),
+            None => uid.to_string(),
+        }
+    }
+}
+//################################################################
+struct CpuSample {
+    start_time: u64,
+    total_time: u64,
+}
+
+/// Remembers the cpu ticks of every process from the previous collector pass, so the usage
+/// can be computed over the refresh interval instead of over the whole process lifetime.
+///
+/// The usage is in the Irix style: 100% means one full core.
+pub struct CpuTracker {
+    hertz: f32,
+    uptime: f32,
+    elapsed_seconds: f32,
+    last_pass: Option<Instant>,
+    previous_samples: HashMap<u32, CpuSample>,
+    current_samples: HashMap<u32, CpuSample>,
+}
+
+impl Default for CpuTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuTracker {
+    /// A tracker without any previous samples.
+    pub fn new() -> Self {
+        Self {
+            hertz: procfs::ticks_per_second() as f32,
+            uptime: 0.,
+            elapsed_seconds: 0.,
+            last_pass: None,
+            previous_samples: HashMap::new(),
+            current_samples: HashMap::new(),
+        }
+    }
+
+    /// Seconds since boot, as read by the last [`CpuTracker::begin_pass`].
+    pub fn uptime(&self) -> f32 {
+        self.uptime
+    }
+
+    /// Starts a pass over the processes. Call it before the first [`CpuTracker::process_usage`].
+    pub fn begin_pass(&mut self, root: &ProcRoot) {
+        let now = Instant::now();
+        self.elapsed_seconds = match self.last_pass {
+            Some(last_pass) => now.duration_since(last_pass).as_secs_f32(),
+            None => 0.,
+        };
+        self.last_pass = Some(now);
+        if let Ok(uptime) = read_uptime(root) {
+            self.uptime = uptime;
+        }
+    }
+
+    /// Ends a pass. The samples of this pass become the previous ones for the next pass.
+    pub fn end_pass(&mut self) {
+        // processes that exited during this pass are dropped here
+        self.previous_samples = std::mem::take(&mut self.current_samples);
+    }
+
+    /// The usage of a process or a thread since the previous pass, from its `stat` file.
+    pub fn process_usage(&mut self, pid: u32, stat: &ProcessStat) -> f32 {
+        //https://stackoverflow.com/questions/16726779/how-do-i-get-the-total-cpu-usage-of-an-application-from-proc-pid-stat
+        //if we want to include children processes, we need cutime and cstime too.
+        let total_time = stat.total_time();
+        let start_time = stat.starttime;
+
+        let usage = match self.previous_samples.get(&pid) {
+            // the start time tells a reused pid apart from the process we sampled before
+            Some(previous) if previous.start_time == start_time && self.elapsed_seconds > 0. => {
+                let ticks = total_time.saturating_sub(previous.total_time) as f32;
+                100_f32 * (ticks / self.hertz) / self.elapsed_seconds
+            }
+            // a process we have not seen before, so the best we know is its lifetime average
+            _ => {
+                let seconds = self.uptime - (start_time as f32 / self.hertz);
+                if seconds > 0. {
+                    100_f32 * ((total_time as f32 / self.hertz) / seconds)
+                } else {
+                    0.
+                }
+            }
+        };
+
+        self.current_samples.insert(
+            pid,
+            CpuSample {
+                start_time,
+                total_time,
+            },
+        );
+        usage
+    }
+}
+//################################################################
+/// Reads everything about one process, with the optional values in `fields`. Values that can
+/// not be read are left empty, or set to one of [`ACCESS_DENIED`], [`NOT_FOUND`] and
+/// [`NOT_AVAILABLE`].
+pub fn get_process_data(
+    root: &ProcRoot,
+    pid: u32,
+    fields: ProcessFields,
+    user_cache: &mut UserCache,
+    cpu_tracker: &mut CpuTracker,
+    io_tracker: &mut IoTracker,
+) -> ProcInfo {
+    let mut proc_info: ProcInfo = ProcInfo {
+        name: String::from(""),
+        user: String::from(""),
+        pid,
+        status: String::from(""),
+        cpu: 0.,
+        memory_used: 0.,
+        path: String::from(""),
+        parent_pid: 0,
+        threads: 0,
+        thread_list: Vec::new(),
+        nice: 0,
+        priority: 0,
+        processor: 0,
+        start_time: 0.,
+        elapsed_time: 0.,
+        cpu_time: 0.,
+        virtual_memory: 0.,
+        shared_memory: 0.,
+        swap: 0.,
+        command_line: String::new(),
+        tty: String::new(),
+        session: 0,
+        cgroup: String::new(),
+        io_read_rate: None,
+        io_write_rate: None,
+        open_files: None,
+        voluntary_context_switches: 0,
+        involuntary_context_switches: 0,
+        io_priority: None,
+        cpu_affinity: Vec::new(),
+    };
+
+    if let Ok(info) = read_process_info(root, pid) {
+        proc_info.status = info.status;
+        proc_info.pid = info.pid;
+        proc_info.memory_used = info.memory_used as f32 / 1024.0;
+        proc_info.name = info.name;
+        proc_info.parent_pid = info.parent_pid;
+        proc_info.virtual_memory = info.virtual_memory as f32 / 1024.0;
+        proc_info.shared_memory = info.shared_memory as f32 / 1024.0;
+        proc_info.swap = info.swap as f32 / 1024.0;
+        proc_info.voluntary_context_switches = info.voluntary_context_switches;
+        proc_info.involuntary_context_switches = info.involuntary_context_switches;
+        proc_info.cpu_affinity = info.cpus_allowed;
+        proc_info.user = match info.uid {
+            Some(uid) => user_cache.user_name(uid),
+            None => String::from(NOT_AVAILABLE),
+        };
+    }
+    if let Ok(stat) = read_process_stat(root, pid) {
+        proc_info.cpu = cpu_tracker.process_usage(pid, &stat);
+        proc_info.threads = stat.num_threads;
+        proc_info.nice = stat.nice;
+        proc_info.priority = stat.priority;
+        proc_info.processor = stat.processor;
+        proc_info.start_time = ProcessStat::ticks_to_seconds(stat.starttime);
+        proc_info.elapsed_time = (cpu_tracker.uptime() - proc_info.start_time).max(0.);
+        proc_info.cpu_time = ProcessStat::ticks_to_seconds(stat.total_time());
+        proc_info.tty = stat.tty_name().unwrap_or_default();
+        proc_info.session = stat.session;
+
+        if fields.io_rates {
+            if let Ok(io) = read_process_io(root, pid) {
+                let (read_rate, write_rate) =
+                    io_tracker.process_rates(pid, stat.starttime, proc_info.elapsed_time, &io);
+                proc_info.io_read_rate = Some(read_rate);
+                proc_info.io_write_rate = Some(write_rate);
+            }
+        }
+    }
+    proc_info.path = match get_process_file_path(root, pid) {
+        Ok(file_path) => file_path,
+        Err(error) if error.is_permission_denied() => String::from(ACCESS_DENIED),
+        Err(_) => String::from(NOT_FOUND),
+    };
+    if fields.command_line {
+        proc_info.command_line = read_command_line(root, pid).unwrap_or_default();
+    }
+    if fields.cgroup {
+        proc_info.cgroup = read_process_cgroup(root, pid).unwrap_or_default();
+    }
+    if fields.open_files {
+        proc_info.open_files = count_open_files(root, pid).ok();
+    }
+    // there is no file for it, and the pids of another root mean other processes here
+    if fields.io_priority && root.is_local() {
+        proc_info.io_priority = read_io_priority(pid).ok();
+    }
+    proc_info
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+/// The directory that procfs is mounted on.
+///
+/// This is `/proc` unless the collectors are pointed somewhere else, like at a fixture directory
+/// in tests or at the `/proc` of the host bind-mounted into a container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcRoot {
+    path: PathBuf,
+    passwd: Option<PathBuf>,
+    is_local: bool,
+}
+
+impl Default for ProcRoot {
+    fn default() -> Self {
+        Self::new("/proc")
+    }
+}
+
+impl ProcRoot {
+    /// A root at `path`.
+    ///
+    /// User names are looked up in `/etc/passwd` for the local `/proc` only. The uids of
+    /// another root belong to another user database, see [`ProcRoot::with_passwd`].
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let is_local = is_local_proc(&path);
+        Self {
+            passwd: is_local.then(|| PathBuf::from("/etc/passwd")),
+            path,
+            is_local,
+        }
+    }
+
+    /// Looks up user names in `passwd` instead, like the `/etc/passwd` of a container.
+    pub fn with_passwd(mut self, passwd: impl Into<PathBuf>) -> Self {
+        self.passwd = Some(passwd.into());
+        self
+    }
+
+    /// The directory itself.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The passwd file that user names are looked up in, `None` to show the uids.
+    pub fn passwd(&self) -> Option<&Path> {
+        self.passwd.as_deref()
+    }
+
+    /// True for the `/proc` of the running system, whose pids can be passed to system calls.
+    pub fn is_local(&self) -> bool {
+        self.is_local
+    }
+
+    /// A file at the top of the root, like `stat` or `meminfo`.
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// A file of one process, like `status` or `task/<pid>/children`.
+    pub fn process_file(&self, pid: u32, name: &str) -> PathBuf {
+        self.path.join(pid.to_string()).join(name)
+    }
+}
+
+// `/proc/`, `/proc/.` and a relative path or symlink to it are all the local `/proc`
+fn is_local_proc(path: &Path) -> bool {
+    match std::fs::canonicalize(path) {
+        Ok(path) => path == Path::new("/proc"),
+        // a root that does not exist (yet) is compared as it was written
+        Err(_) => path.components().eq(Path::new("/proc").components()),
+    }
+}
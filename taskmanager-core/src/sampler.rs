@@ -0,0 +1,180 @@
+use crate::cpu::{read_cpu_usage, CpuTimes, CpuUsage};
+use crate::error::Result;
+use crate::disk::{read_disk_usage, DiskSample, DiskUsage};
+use crate::files;
+use crate::io::IoTracker;
+use crate::memory::{read_memory_usage, MemoryInfo};
+use crate::net::{read_network_usage, InterfaceUsage, NetworkSample};
+use crate::process::{get_process_data, CpuTracker, ProcInfo, ProcessFields, UserCache};
+use crate::root::ProcRoot;
+use crate::thread::read_threads;
+use crate::tree::ProcessTree;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Everything collected in one pass.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    /// Every process, by pid.
+    pub processes: BTreeMap<u32, ProcInfo>,
+    /// The parent to children index of the processes.
+    pub tree: ProcessTree,
+    /// The usage of the whole machine and of every core.
+    pub cpu: CpuUsage,
+    /// The memory usage.
+    pub memory: MemoryInfo,
+    /// The usage of every block device, including partitions.
+    pub disks: Vec<DiskUsage>,
+    /// The throughput of every network interface.
+    pub network: Vec<InterfaceUsage>,
+    /// When the snapshot was taken.
+    pub taken_at: Instant,
+}
+
+/// Collects snapshots of the processes and of the whole machine.
+///
+/// The cpu usage of the processes and of the cores, the disk usage and the network throughput
+/// are measured between two calls, so a sampler should be kept and called again on every
+/// refresh. The first call measures them since boot (or since the start of each process).
+///
+/// ```no_run
+/// let mut sampler = taskmanager_core::Sampler::new();
+/// let snapshot = sampler.sample()?;
+/// for process in snapshot.processes.values() {
+///     println!("{} {} {:.2}%", process.pid, process.name, process.cpu);
+/// }
+/// # Ok::<(), taskmanager_core::Error>(())
+/// ```
+#[derive(Default)]
+pub struct Sampler {
+    root: ProcRoot,
+    user_cache: UserCache,
+    cpu_tracker: CpuTracker,
+    io_tracker: IoTracker,
+    collect_threads: bool,
+    fields: ProcessFields,
+    thread_cpu_tracker: CpuTracker,
+    previous_cpu_times: Vec<CpuTimes>,
+    previous_disk_sample: DiskSample,
+    previous_network_sample: NetworkSample,
+}
+
+impl Sampler {
+    /// A sampler of `/proc`, without any previous samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A sampler of another procfs root, like a fixture directory or the `/proc` of the host.
+    pub fn with_root(root: ProcRoot) -> Self {
+        Self {
+            user_cache: UserCache::new(root.passwd()),
+            root,
+            ..Self::default()
+        }
+    }
+
+    /// Also collect the threads of every process into [`ProcInfo::thread_list`]. Off by default,
+    /// since it reads one more file per thread.
+    ///
+    /// It can be switched on and off between passes, when the threads are needed.
+    pub fn set_collect_threads(&mut self, collect_threads: bool) {
+        // the thread usage after a gap would be measured over the gap
+        if self.collect_threads && !collect_threads {
+            self.thread_cpu_tracker = CpuTracker::new();
+        }
+        self.collect_threads = collect_threads;
+    }
+
+    /// The optional values of the processes to collect, all of them by default. Like the
+    /// threads, they can be changed between passes.
+    pub fn set_fields(&mut self, fields: ProcessFields) {
+        self.fields = fields;
+    }
+
+    /// The procfs root that is sampled.
+    pub fn root(&self) -> &ProcRoot {
+        &self.root
+    }
+
+    /// Collects the processes, the cpu usage, the memory usage, the disk usage and the network
+    /// throughput.
+    ///
+    /// A root without a readable `diskstats` or `net/dev`, like some containers, has no disks or
+    /// interfaces in the snapshot.
+    pub fn sample(&mut self) -> Result<Snapshot> {
+        let processes = self.processes()?;
+        Ok(Snapshot {
+            tree: ProcessTree::build(&processes),
+            processes,
+            cpu: self.cpu_usage()?,
+            memory: self.memory_info()?,
+            disks: self.disk_usage().unwrap_or_default(),
+            network: self.network_usage().unwrap_or_default(),
+            taken_at: Instant::now(),
+        })
+    }
+
+    /// One pass over every process in the procfs root.
+    pub fn processes(&mut self) -> Result<BTreeMap<u32, ProcInfo>> {
+        let mut process_map: BTreeMap<u32, ProcInfo> = BTreeMap::new();
+        let entries = files::read_dir(self.root.path())?;
+        self.cpu_tracker.begin_pass(&self.root);
+        self.io_tracker.begin_pass();
+        if self.collect_threads {
+            self.thread_cpu_tracker.begin_pass(&self.root);
+        }
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            if let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() {
+                let mut proc_info = get_process_data(
+                    &self.root,
+                    pid,
+                    self.fields,
+                    &mut self.user_cache,
+                    &mut self.cpu_tracker,
+                    &mut self.io_tracker,
+                );
+                if self.collect_threads {
+                    // the process may have exited since we read its status
+                    if let Ok(threads) = read_threads(&self.root, pid, &mut self.thread_cpu_tracker) {
+                        proc_info.thread_list = threads;
+                    }
+                }
+                process_map.insert(pid, proc_info);
+            }
+        }
+        self.cpu_tracker.end_pass();
+        self.io_tracker.end_pass();
+        if self.collect_threads {
+            self.thread_cpu_tracker.end_pass();
+        }
+        Ok(process_map)
+    }
+
+    /// Seconds since boot at the start of the last [`Sampler::processes`], to compare the start
+    /// times of the processes with.
+    pub fn uptime(&self) -> f32 {
+        self.cpu_tracker.uptime()
+    }
+
+    /// The cpu usage since the previous call.
+    pub fn cpu_usage(&mut self) -> Result<CpuUsage> {
+        read_cpu_usage(&self.root, &mut self.previous_cpu_times)
+    }
+
+    /// The disk usage since the previous call.
+    pub fn disk_usage(&mut self) -> Result<Vec<DiskUsage>> {
+        read_disk_usage(&self.root, &mut self.previous_disk_sample)
+    }
+
+    /// The network throughput since the previous call.
+    pub fn network_usage(&mut self) -> Result<Vec<InterfaceUsage>> {
+        read_network_usage(&self.root, &mut self.previous_network_sample)
+    }
+
+    /// The current memory usage.
+    pub fn memory_info(&self) -> Result<MemoryInfo> {
+        read_memory_usage(&self.root)
+    }
+}
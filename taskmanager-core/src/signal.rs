@@ -0,0 +1,89 @@
+use crate::error::{Error, Result};
+
+/// The signals that can be sent to a process from the process list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessSignal {
+    /// SIGTERM, asks the process to exit.
+    Terminate,
+    /// SIGKILL, ends the process right away.
+    Kill,
+    /// SIGSTOP, pauses the process.
+    Stop,
+    /// SIGCONT, resumes a stopped process.
+    Continue,
+}
+
+impl ProcessSignal {
+    /// Every signal, in the order they are offered to the user.
+    pub const ALL: [ProcessSignal; 4] = [
+        ProcessSignal::Terminate,
+        ProcessSignal::Kill,
+        ProcessSignal::Stop,
+        ProcessSignal::Continue,
+    ];
+
+    /// The action, for buttons and menus.
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessSignal::Terminate => "Terminate",
+            ProcessSignal::Kill => "Kill",
+            ProcessSignal::Stop => "Stop",
+            ProcessSignal::Continue => "Continue",
+        }
+    }
+
+    /// The name of the signal, like `SIGTERM`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProcessSignal::Terminate => "SIGTERM",
+            ProcessSignal::Kill => "SIGKILL",
+            ProcessSignal::Stop => "SIGSTOP",
+            ProcessSignal::Continue => "SIGCONT",
+        }
+    }
+
+    /// The signal number.
+    pub fn as_raw(self) -> libc::c_int {
+        match self {
+            ProcessSignal::Terminate => libc::SIGTERM,
+            ProcessSignal::Kill => libc::SIGKILL,
+            ProcessSignal::Stop => libc::SIGSTOP,
+            ProcessSignal::Continue => libc::SIGCONT,
+        }
+    }
+
+    /// Terminate and kill end the process, so the user should confirm them first.
+    pub fn is_destructive(self) -> bool {
+        matches!(self, ProcessSignal::Terminate | ProcessSignal::Kill)
+    }
+}
+//################################################################
+/// Sends a signal to one process.
+pub fn send_signal(pid: u32, signal: ProcessSignal) -> Result<()> {
+    let syscall_error = |source| Error::Syscall {
+        call: "kill",
+        pid,
+        source,
+    };
+    // pid 0 or a negative pid would signal a whole process group
+    let raw_pid = match libc::pid_t::try_from(pid) {
+        Ok(raw_pid) if raw_pid > 0 => raw_pid,
+        _ => return Err(syscall_error(std::io::Error::from_raw_os_error(libc::ESRCH))),
+    };
+    if unsafe { libc::kill(raw_pid, signal.as_raw()) } == 0 {
+        Ok(())
+    } else {
+        Err(syscall_error(std::io::Error::last_os_error()))
+    }
+}
+
+/// A short reason for a failed system call on a process, naming the usual errno values.
+pub fn describe_syscall_error(error: &Error) -> String {
+    match error.raw_os_error() {
+        Some(libc::EPERM) => String::from("permission denied (EPERM)"),
+        Some(libc::EACCES) => String::from("permission denied (EACCES)"),
+        Some(libc::ESRCH) => String::from("no such process (ESRCH)"),
+        Some(libc::EINVAL) => String::from("invalid argument (EINVAL)"),
+        _ => error.to_string(),
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::Result;
+use crate::files;
+use crate::root::ProcRoot;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+// the flag of a unix socket that accepts connections
+const UNIX_ACCEPT_CONNECTIONS: u32 = 0x10000;
+
+/// The socket tables of `/proc/net`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum SocketProtocol {
+    /// TCP over IPv4.
+    Tcp,
+    /// TCP over IPv6.
+    Tcp6,
+    /// UDP over IPv4.
+    Udp,
+    /// UDP over IPv6.
+    Udp6,
+    /// Unix domain sockets.
+    Unix,
+}
+
+impl SocketProtocol {
+    /// Every table, in the order they are read.
+    pub const ALL: [SocketProtocol; 5] = [
+        SocketProtocol::Tcp,
+        SocketProtocol::Tcp6,
+        SocketProtocol::Udp,
+        SocketProtocol::Udp6,
+        SocketProtocol::Unix,
+    ];
+
+    /// The name of the table in `/proc/net`, like `tcp6`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SocketProtocol::Tcp => "tcp",
+            SocketProtocol::Tcp6 => "tcp6",
+            SocketProtocol::Udp => "udp",
+            SocketProtocol::Udp6 => "udp6",
+            SocketProtocol::Unix => "unix",
+        }
+    }
+}
+
+/// One line of a socket table.
+#[derive(Clone, Debug, Serialize)]
+pub struct Socket {
+    /// The table the socket was found in.
+    pub protocol: SocketProtocol,
+    /// The inode that the `socket:[inode]` links in `/proc/<pid>/fd` point to.
+    pub inode: u64,
+    /// The local IP address, or the path of a unix socket. Empty for unnamed unix sockets.
+    pub local_address: String,
+    /// The local port, 0 for unix sockets.
+    pub local_port: u16,
+    /// The remote IP address, empty for unix sockets.
+    pub remote_address: String,
+    /// The remote port, 0 for unix sockets.
+    pub remote_port: u16,
+    /// The state, like `LISTEN` or `ESTABLISHED`, or `UNCONN` for unconnected udp sockets.
+    pub state: String,
+}
+
+impl Socket {
+    /// Parses a line of `/proc/net/tcp`, `tcp6`, `udp` or `udp6`. `None` for the header line.
+    pub fn parse_inet(protocol: SocketProtocol, line: &str) -> Option<Self> {
+        // sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (local_address, local_port) = parse_inet_address(fields.get(1)?)?;
+        let (remote_address, remote_port) = parse_inet_address(fields.get(2)?)?;
+        let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+        let is_udp = matches!(protocol, SocketProtocol::Udp | SocketProtocol::Udp6);
+        Some(Socket {
+            protocol,
+            inode: fields.get(9)?.parse().ok()?,
+            local_address,
+            local_port,
+            remote_address,
+            remote_port,
+            state: inet_state_name(state, is_udp).to_string(),
+        })
+    }
+
+    /// Parses a line of `/proc/net/unix`. `None` for the header line.
+    pub fn parse_unix(line: &str) -> Option<Self> {
+        // Num RefCount Protocol Flags Type St Inode Path
+        let mut fields = line.split_whitespace();
+        let flags = u32::from_str_radix(fields.nth(3)?, 16).ok()?;
+        let state = u8::from_str_radix(fields.nth(1)?, 16).ok()?;
+        let inode = fields.next()?.parse().ok()?;
+        let path: Vec<&str> = fields.collect();
+        let state = match state {
+            _ if flags & UNIX_ACCEPT_CONNECTIONS != 0 => "LISTEN",
+            1 => "UNCONNECTED",
+            2 => "CONNECTING",
+            3 => "CONNECTED",
+            4 => "DISCONNECTING",
+            _ => "UNKNOWN",
+        };
+        Some(Socket {
+            protocol: SocketProtocol::Unix,
+            inode,
+            local_address: path.join(" "),
+            local_port: 0,
+            remote_address: String::new(),
+            remote_port: 0,
+            state: state.to_string(),
+        })
+    }
+
+    /// The local end, like `127.0.0.1:631`, `[::1]:22` or the path of a unix socket.
+    pub fn local(&self) -> String {
+        self.endpoint(&self.local_address, self.local_port)
+    }
+
+    /// The remote end, like [`Socket::local`]. Empty for unix sockets.
+    pub fn remote(&self) -> String {
+        self.endpoint(&self.remote_address, self.remote_port)
+    }
+
+    fn endpoint(&self, address: &str, port: u16) -> String {
+        match self.protocol {
+            SocketProtocol::Unix => address.to_string(),
+            SocketProtocol::Tcp6 | SocketProtocol::Udp6 => format!("[{}]:{}", address, port),
+            SocketProtocol::Tcp | SocketProtocol::Udp => format!("{}:{}", address, port),
+        }
+    }
+}
+
+/// A socket together with the processes that have it open.
+#[derive(Clone, Debug, Serialize)]
+pub struct Connection {
+    /// The socket.
+    pub socket: Socket,
+    /// The processes with a file descriptor of the socket, usually one. Empty when the owner
+    /// can not be read, or lives in another network namespace.
+    pub pids: Vec<u32>,
+}
+//################################################################
+// The address is written as hex words in host byte order, followed by the port: 0100007F:0277
+fn parse_inet_address(field: &str) -> Option<(String, u16)> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut words = Vec::with_capacity(4);
+    for index in (0..address.len()).step_by(8) {
+        words.push(u32::from_str_radix(address.get(index..index + 8)?, 16).ok()?);
+    }
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+    let address = match bytes.len() {
+        4 => Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).to_string(),
+        16 => Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string(),
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+fn inet_state_name(state: u8, is_udp: bool) -> &'static str {
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        // a udp socket without a connected peer
+        0x07 if is_udp => "UNCONN",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+//################################################################
+// Reads every table in a net directory. Tables that are missing, like tcp6 without IPv6, are
+// skipped; it fails only when none of them can be read.
+fn read_socket_tables(net_dir: &Path) -> Result<Vec<Socket>> {
+    let mut sockets = Vec::new();
+    let mut first_error = None;
+    let mut any_table_read = false;
+    for protocol in SocketProtocol::ALL {
+        match files::read_to_string(net_dir.join(protocol.name())) {
+            Ok(table) => {
+                any_table_read = true;
+                sockets.extend(table.lines().filter_map(|line| match protocol {
+                    SocketProtocol::Unix => Socket::parse_unix(line),
+                    _ => Socket::parse_inet(protocol, line),
+                }));
+            }
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    match first_error {
+        Some(error) if !any_table_read => Err(error),
+        _ => Ok(sockets),
+    }
+}
+//################################################################
+/// Reads every socket of `/proc/net`, which are the ones of the network namespace of the
+/// reading process.
+pub fn read_sockets(root: &ProcRoot) -> Result<Vec<Socket>> {
+    read_socket_tables(&root.file("net"))
+}
+//################################################################
+/// The inode of a `socket:[inode]` link target in `/proc/<pid>/fd`.
+pub fn socket_inode(link_target: &str) -> Option<u64> {
+    link_target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+//################################################################
+/// The sockets a process has open, as `(fd, socket)` pairs ordered by fd.
+///
+/// They are looked up in the tables of the network namespace of the process. Sockets that are
+/// in none of the tables, like netlink sockets, are left out.
+pub fn read_process_sockets(root: &ProcRoot, pid: u32) -> Result<Vec<(u32, Socket)>> {
+    let mut inodes = Vec::new();
+    for entry in files::read_dir(root.process_file(pid, "fd"))?.filter_map(|e| e.ok()) {
+        let Ok(fd) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // the file may have been closed since the directory was read
+        if let Ok(target) = std::fs::read_link(entry.path()) {
+            if let Some(inode) = socket_inode(&target.to_string_lossy()) {
+                inodes.push((fd, inode));
+            }
+        }
+    }
+    inodes.sort_by_key(|(fd, _)| *fd);
+    if inodes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sockets: HashMap<u64, Socket> = read_socket_tables(&root.process_file(pid, "net"))?
+        .into_iter()
+        .map(|socket| (socket.inode, socket))
+        .collect();
+    Ok(inodes
+        .into_iter()
+        .filter_map(|(fd, inode)| Some((fd, sockets.get(&inode)?.clone())))
+        .collect())
+}
+//################################################################
+/// Every socket of `/proc/net` with the processes that have it open.
+///
+/// Finding the owners reads the `fd` directory of every process, so this is much slower than
+/// [`read_sockets`]. Only the processes the current user may inspect are found.
+pub fn read_connections(root: &ProcRoot) -> Result<Vec<Connection>> {
+    let sockets = read_sockets(root)?;
+
+    let mut owners: HashMap<u64, Vec<u32>> = HashMap::new();
+    for process_entry in files::read_dir(root.path())?.filter_map(|e| e.ok()) {
+        let Ok(pid) = process_entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        let Ok(fd_entries) = std::fs::read_dir(process_entry.path().join("fd")) else {
+            continue;
+        };
+        for fd_entry in fd_entries.filter_map(|e| e.ok()) {
+            if let Ok(target) = std::fs::read_link(fd_entry.path()) {
+                if let Some(inode) = socket_inode(&target.to_string_lossy()) {
+                    let pids = owners.entry(inode).or_default();
+                    // a process can hold the same socket in several file descriptors
+                    if pids.last() != Some(&pid) {
+                        pids.push(pid);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(sockets
+        .into_iter()
+        .map(|socket| {
+            let mut pids = owners.get(&socket.inode).cloned().unwrap_or_default();
+            pids.sort_unstable();
+            Connection { socket, pids }
+        })
+        .collect())
+}
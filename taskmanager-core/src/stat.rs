@@ -0,0 +1,229 @@
+use crate::error::{Error, Result};
+use crate::files;
+use crate::root::ProcRoot;
+use std::str::FromStr;
+
+/// Every field of `/proc/<pid>/stat`, named as in proc(5).
+///
+/// Times are in clock ticks, see [`ProcessStat::ticks_to_seconds`]. Fields that older kernels do
+/// not write, or that can not be parsed, are read as 0.
+#[derive(Clone, Default, Debug)]
+pub struct ProcessStat {
+    /// (1) The process id.
+    pub pid: i32,
+    /// (2) The command name, without the parentheses around it.
+    pub comm: String,
+    /// (3) The one letter state, like `R` or `S`.
+    pub state: char,
+    /// (4) The pid of the parent.
+    pub ppid: i32,
+    /// (5) The process group id.
+    pub pgrp: i32,
+    /// (6) The session id.
+    pub session: i32,
+    /// (7) The controlling terminal.
+    pub tty_nr: i32,
+    /// (8) The foreground process group of the controlling terminal.
+    pub tpgid: i32,
+    /// (9) The kernel flags word.
+    pub flags: u32,
+    /// (10) Minor faults.
+    pub minflt: u64,
+    /// (11) Minor faults of the waited-for children.
+    pub cminflt: u64,
+    /// (12) Major faults.
+    pub majflt: u64,
+    /// (13) Major faults of the waited-for children.
+    pub cmajflt: u64,
+    /// (14) Time spent in user mode.
+    pub utime: u64,
+    /// (15) Time spent in kernel mode.
+    pub stime: u64,
+    /// (16) Time the waited-for children spent in user mode.
+    pub cutime: i64,
+    /// (17) Time the waited-for children spent in kernel mode.
+    pub cstime: i64,
+    /// (18) The kernel priority.
+    pub priority: i64,
+    /// (19) The nice value, from -20 to 19.
+    pub nice: i64,
+    /// (20) The number of threads.
+    pub num_threads: i64,
+    /// (21) Always 0 since Linux 2.6.17.
+    pub itrealvalue: i64,
+    /// (22) The time the process started after boot.
+    pub starttime: u64,
+    /// (23) Virtual memory size, in bytes.
+    pub vsize: u64,
+    /// (24) Resident set size, in pages.
+    pub rss: i64,
+    /// (25) The soft limit of the resident set size, in bytes.
+    pub rsslim: u64,
+    /// (26) The address above which the program text can run.
+    pub startcode: u64,
+    /// (27) The address below which the program text can run.
+    pub endcode: u64,
+    /// (28) The start of the stack.
+    pub startstack: u64,
+    /// (29) The current stack pointer.
+    pub kstkesp: u64,
+    /// (30) The current instruction pointer.
+    pub kstkeip: u64,
+    /// (31) Obsolete pending signals bitmap.
+    pub signal: u64,
+    /// (32) Obsolete blocked signals bitmap.
+    pub blocked: u64,
+    /// (33) Obsolete ignored signals bitmap.
+    pub sigignore: u64,
+    /// (34) Obsolete caught signals bitmap.
+    pub sigcatch: u64,
+    /// (35) The channel the process is waiting in.
+    pub wchan: u64,
+    /// (36) Not maintained.
+    pub nswap: u64,
+    /// (37) Not maintained.
+    pub cnswap: u64,
+    /// (38) The signal sent to the parent when the process dies.
+    pub exit_signal: i32,
+    /// (39) The cpu the process last ran on.
+    pub processor: i32,
+    /// (40) The real-time priority, 0 for normal processes.
+    pub rt_priority: u32,
+    /// (41) The scheduling policy.
+    pub policy: u32,
+    /// (42) Aggregated block io delays.
+    pub delayacct_blkio_ticks: u64,
+    /// (43) Time spent running a virtual cpu for a guest.
+    pub guest_time: u64,
+    /// (44) Guest time of the waited-for children.
+    pub cguest_time: i64,
+    /// (45) The address above which initialized and uninitialized data are placed.
+    pub start_data: u64,
+    /// (46) The address below which initialized and uninitialized data are placed.
+    pub end_data: u64,
+    /// (47) The address above which the heap can be expanded.
+    pub start_brk: u64,
+    /// (48) The address above which the command line arguments are placed.
+    pub arg_start: u64,
+    /// (49) The address below which the command line arguments are placed.
+    pub arg_end: u64,
+    /// (50) The address above which the environment is placed.
+    pub env_start: u64,
+    /// (51) The address below which the environment is placed.
+    pub env_end: u64,
+    /// (52) The exit status, in the form reported by waitpid.
+    pub exit_code: i32,
+}
+
+impl ProcessStat {
+    /// Parses the content of `/proc/<pid>/stat`.
+    ///
+    /// The command name can contain spaces and parentheses, so it is everything between the
+    /// first `(` and the last `)`. Fails only when there are no parentheses at all.
+    pub fn parse(stat_content: &str) -> std::result::Result<Self, String> {
+        let (pid, rest) = stat_content.split_once('(').ok_or("missing command name")?;
+        let (comm, rest) = rest.rsplit_once(')').ok_or("missing command name")?;
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+
+        // fields[0] is the state, which is field 3 in proc(5)
+        fn field<T: FromStr + Default>(fields: &[&str], number: usize) -> T {
+            fields
+                .get(number - 3)
+                .and_then(|value| value.parse::<T>().ok())
+                .unwrap_or_default()
+        }
+        let field_u64 = |number: usize| field::<u64>(&fields, number);
+        let field_i64 = |number: usize| field::<i64>(&fields, number);
+        let field_i32 = |number: usize| field::<i32>(&fields, number);
+
+        Ok(ProcessStat {
+            pid: pid.trim().parse().unwrap_or_default(),
+            comm: comm.to_string(),
+            state: fields.first().and_then(|state| state.chars().next()).unwrap_or('?'),
+            ppid: field_i32(4),
+            pgrp: field_i32(5),
+            session: field_i32(6),
+            tty_nr: field_i32(7),
+            tpgid: field_i32(8),
+            flags: field(&fields, 9),
+            minflt: field_u64(10),
+            cminflt: field_u64(11),
+            majflt: field_u64(12),
+            cmajflt: field_u64(13),
+            utime: field_u64(14),
+            stime: field_u64(15),
+            cutime: field_i64(16),
+            cstime: field_i64(17),
+            priority: field_i64(18),
+            nice: field_i64(19),
+            num_threads: field_i64(20),
+            itrealvalue: field_i64(21),
+            starttime: field_u64(22),
+            vsize: field_u64(23),
+            rss: field_i64(24),
+            rsslim: field_u64(25),
+            startcode: field_u64(26),
+            endcode: field_u64(27),
+            startstack: field_u64(28),
+            kstkesp: field_u64(29),
+            kstkeip: field_u64(30),
+            signal: field_u64(31),
+            blocked: field_u64(32),
+            sigignore: field_u64(33),
+            sigcatch: field_u64(34),
+            wchan: field_u64(35),
+            nswap: field_u64(36),
+            cnswap: field_u64(37),
+            exit_signal: field_i32(38),
+            processor: field_i32(39),
+            rt_priority: field(&fields, 40),
+            policy: field(&fields, 41),
+            delayacct_blkio_ticks: field_u64(42),
+            guest_time: field_u64(43),
+            cguest_time: field_i64(44),
+            start_data: field_u64(45),
+            end_data: field_u64(46),
+            start_brk: field_u64(47),
+            arg_start: field_u64(48),
+            arg_end: field_u64(49),
+            env_start: field_u64(50),
+            env_end: field_u64(51),
+            exit_code: field_i32(52),
+        })
+    }
+
+    /// Time spent in user and kernel mode together, in clock ticks.
+    pub fn total_time(&self) -> u64 {
+        self.utime + self.stime
+    }
+
+    /// The name of the controlling terminal, like `pts/3` or `tty1`, without `/dev/`. `None`
+    /// when the process has no terminal.
+    pub fn tty_name(&self) -> Option<String> {
+        if self.tty_nr == 0 {
+            return None;
+        }
+        // the minor number is split around the major one
+        let tty_nr = self.tty_nr as u32;
+        let major = (tty_nr >> 8) & 0xfff;
+        let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+        Some(match major {
+            4 if minor < 64 => format!("tty{}", minor),
+            4 => format!("ttyS{}", minor - 64),
+            136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+            _ => format!("{}:{}", major, minor),
+        })
+    }
+
+    /// Converts clock ticks, like [`ProcessStat::starttime`], to seconds.
+    pub fn ticks_to_seconds(ticks: u64) -> f32 {
+        ticks as f32 / procfs::ticks_per_second() as f32
+    }
+}
+//################################################################
+/// Reads `/proc/<pid>/stat`.
+pub fn read_process_stat(root: &ProcRoot, pid: u32) -> Result<ProcessStat> {
+    let stat_path = root.process_file(pid, "stat");
+    let stat_content = files::read_to_string(&stat_path)?;
+    ProcessStat::parse(&stat_content).map_err(|message| Error::parse(stat_path, message))
+}
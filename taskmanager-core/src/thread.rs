@@ -0,0 +1,50 @@
+use crate::error::Result;
+use crate::files;
+use crate::process::CpuTracker;
+use crate::root::ProcRoot;
+use crate::stat::ProcessStat;
+use serde::Serialize;
+
+/// One thread of a process, from `/proc/<pid>/task/<tid>/stat`.
+#[derive(Clone, Serialize, Debug)]
+pub struct ThreadInfo {
+    /// The thread id. The main thread has the pid of the process.
+    pub tid: u32,
+    /// The thread name, which the program can set separately from the process name.
+    pub name: String,
+    /// The one letter state, like `R` or `S`.
+    pub state: String,
+    /// Percent of one core over the last refresh interval.
+    pub cpu: f32,
+    /// The cpu the thread last ran on.
+    pub processor: i32,
+}
+//################################################################
+/// Reads every thread of a process, ordered by tid.
+///
+/// `cpu_tracker` measures the usage of the threads, so it should be kept for threads only:
+/// the main thread has the same id as its process.
+pub fn read_threads(root: &ProcRoot, pid: u32, cpu_tracker: &mut CpuTracker) -> Result<Vec<ThreadInfo>> {
+    let mut threads = Vec::new();
+    for entry in files::read_dir(root.process_file(pid, "task"))?.filter_map(|e| e.ok()) {
+        let Ok(tid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // the thread may have exited while we were reading the directory
+        let Ok(stat_content) = files::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        let Ok(stat) = ProcessStat::parse(&stat_content) else {
+            continue;
+        };
+        threads.push(ThreadInfo {
+            tid,
+            name: stat.comm.clone(),
+            state: stat.state.to_string(),
+            cpu: cpu_tracker.process_usage(tid, &stat),
+            processor: stat.processor,
+        });
+    }
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(threads)
+}
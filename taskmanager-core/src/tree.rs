@@ -0,0 +1,39 @@
+use crate::process::ProcInfo;
+use std::collections::{BTreeMap, HashMap};
+
+/// The parent to children index of one pass over the processes.
+///
+/// It is built from [`ProcInfo::parent_pid`], so unlike the `children` files of procfs it also
+/// knows the children that were started by threads other than the main one.
+#[derive(Clone, Default, Debug)]
+pub struct ProcessTree {
+    roots: Vec<u32>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTree {
+    /// Indexes the processes. The children of every process are ordered by pid.
+    pub fn build(processes: &BTreeMap<u32, ProcInfo>) -> Self {
+        let mut tree = ProcessTree::default();
+        // the map is ordered by pid, so every list of children is too
+        for process in processes.values() {
+            // a parent outside of the map is 0 for the roots, or a process in another pid namespace
+            if process.parent_pid != process.pid && processes.contains_key(&process.parent_pid) {
+                tree.children.entry(process.parent_pid).or_default().push(process.pid);
+            } else {
+                tree.roots.push(process.pid);
+            }
+        }
+        tree
+    }
+
+    /// The processes without a parent in the map, like `init` and `kthreadd`.
+    pub fn roots(&self) -> &[u32] {
+        &self.roots
+    }
+
+    /// The direct children of a process.
+    pub fn children(&self, pid: u32) -> &[u32] {
+        self.children.get(&pid).map_or(&[], Vec::as_slice)
+    }
+}
@@ -0,0 +1,556 @@
+// Runs the collectors against the fake procfs trees in tests/fixtures.
+use taskmanager_core::{
+    find_handles, format_cpu_list, get_children_processes, parse_cpu_list, read_connections, read_cpu_usage,
+    read_disk_stats, read_disk_usage, read_io_priority, read_memory_usage, read_network_stats, read_network_usage,
+    read_process_info, read_process_io, read_process_sockets, read_process_stat, read_sockets, read_threads,
+    read_uptime, set_cpu_affinity, set_nice, socket_inode, AccessMode, DiskSample, HandleKind, IoPriority,
+    IoPriorityClass, IoTracker, NetworkSample, ProcRoot, ProcessDetails, ProcessFields, ProcessStat, ProcessTree,
+    Sampler, SocketProtocol, ACCESS_DENIED, NOT_AVAILABLE, NOT_FOUND,
+};
+
+fn fixture(name: &str) -> ProcRoot {
+    ProcRoot::new(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name))
+}
+
+fn hertz() -> f32 {
+    procfs::ticks_per_second() as f32
+}
+
+// the usage of a process that was not sampled before, the average over its lifetime
+fn lifetime_usage(total_ticks: u64, start_ticks: u64) -> f32 {
+    let uptime = 1000.;
+    100. * (total_ticks as f32 / hertz()) / (uptime - start_ticks as f32 / hertz())
+}
+
+fn assert_close(left: f32, right: f32) {
+    assert!((left - right).abs() < 0.001, "{} != {}", left, right);
+}
+
+#[test]
+fn reads_every_numeric_entry() {
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    let processes = sampler.processes().unwrap();
+
+    let pids: Vec<u32> = processes.keys().copied().collect();
+    assert_eq!(pids, [1, 42, 100, 101, 200, 300, 500]);
+    assert_eq!(get_children_processes(&fixture("proc"), 1).unwrap(), [42, 100, 101, 200, 300]);
+    assert_eq!(processes[&1].path, "/usr/lib/systemd/systemd");
+    assert_eq!(processes[&1].memory_used, 12.);
+}
+
+#[test]
+fn names_with_spaces() {
+    let root = fixture("proc");
+    let status = read_process_info(&root, 42).unwrap();
+    assert_eq!(status.name, "Web Content");
+    assert_eq!(status.status, "R");
+    assert_eq!(status.parent_pid, 1);
+    assert_eq!(status.uid, Some(1000));
+
+    let stat = read_process_stat(&root, 42).unwrap();
+    assert_eq!(stat.comm, "Web Content");
+    assert_eq!(stat.state, 'R');
+    assert_eq!((stat.utime, stat.stime, stat.starttime), (400, 100, 50000));
+}
+
+#[test]
+fn names_with_parentheses() {
+    let root = fixture("proc");
+    assert_eq!(read_process_info(&root, 100).unwrap().name, "(sd-pam)");
+    assert_eq!(read_process_stat(&root, 100).unwrap().comm, "(sd-pam)");
+    assert_eq!(read_process_stat(&root, 100).unwrap().starttime, 300);
+
+    // the fields are counted from the last ')', not from the first one
+    assert_eq!(read_process_info(&root, 101).unwrap().name, "evil) R 7 7 (");
+    let stat = read_process_stat(&root, 101).unwrap();
+    assert_eq!(stat.comm, "evil) R 7 7 (");
+    assert_eq!((stat.state, stat.ppid), ('S', 1));
+    assert_eq!((stat.total_time(), stat.starttime), (50, 400));
+}
+
+#[test]
+fn cpu_usage_of_odd_names() {
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    let processes = sampler.processes().unwrap();
+
+    assert_close(processes[&1].cpu, lifetime_usage(150, 0));
+    assert_close(processes[&42].cpu, lifetime_usage(500, 50000));
+    assert_close(processes[&101].cpu, lifetime_usage(50, 400));
+
+    // nothing ran between the two passes
+    let processes = sampler.processes().unwrap();
+    assert!(processes.values().all(|process| process.cpu == 0.));
+}
+
+#[test]
+fn zombie_processes() {
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    let zombie = &sampler.processes().unwrap()[&200];
+
+    assert_eq!(zombie.name, "defunct child");
+    assert_eq!(zombie.status, "Z");
+    assert_eq!(zombie.memory_used, 0.);
+    assert_eq!(zombie.path, NOT_FOUND);
+    assert!(sampler.processes().unwrap().values().all(|process| process.parent_pid != 200));
+}
+
+#[test]
+fn missing_fields() {
+    let root = fixture("proc");
+    let status = read_process_info(&root, 300).unwrap();
+    assert_eq!(status.uid, None);
+    assert_eq!(status.parent_pid, 0);
+    assert_eq!(status.memory_used, 0);
+    let stat = read_process_stat(&root, 300).unwrap();
+    assert_eq!((stat.state, stat.ppid), ('S', 1));
+    assert_eq!((stat.total_time(), stat.starttime, stat.num_threads), (0, 0, 0));
+
+    let mut sampler = Sampler::with_root(root);
+    let process = &sampler.processes().unwrap()[&300];
+    assert_eq!(process.user, NOT_AVAILABLE);
+    assert_eq!(process.cpu, 0.);
+    assert_ne!(process.path, ACCESS_DENIED);
+}
+
+#[test]
+fn missing_processes() {
+    let root = fixture("proc");
+    assert!(read_process_info(&root, 999).unwrap_err().is_not_found());
+    assert!(read_process_stat(&root, 999).unwrap_err().is_not_found());
+}
+
+#[test]
+fn cpu_usage_since_boot() {
+    let cpu = read_cpu_usage(&fixture("proc"), &mut Vec::new()).unwrap();
+    assert_close(cpu.total.busy, 30.);
+    assert_close(cpu.total.user, 20.);
+    assert_close(cpu.total.system, 10.);
+    assert_close(cpu.total.iowait, 5.);
+
+    // cpu1 only has the first four columns, the missing ones are 0
+    assert_eq!(cpu.cores.len(), 2);
+    assert_close(cpu.cores[0].busy, 30.);
+    assert_close(cpu.cores[1].busy, 30.);
+    assert_close(cpu.cores[1].iowait, 0.);
+}
+
+#[test]
+fn memory_without_mem_available() {
+    let memory = read_memory_usage(&fixture("proc")).unwrap();
+    assert_eq!(memory.total, 16_000_000);
+    // estimated from MemFree, Buffers and Cached
+    assert_eq!(memory.available, 5_500_000);
+    assert_eq!(memory.used(), 10_500_000);
+    assert_eq!(memory.swap_used(), 1_000_000);
+    assert_eq!(memory.hugepages_free, 0);
+}
+
+#[test]
+fn uptime() {
+    assert_eq!(read_uptime(&fixture("proc")).unwrap(), 1000.);
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    sampler.processes().unwrap();
+    assert_eq!(sampler.uptime(), 1000.);
+}
+
+#[test]
+fn process_details() {
+    let details = ProcessDetails::read(&fixture("proc"), 42);
+    assert_eq!(
+        details.command_line.unwrap(),
+        "/usr/lib/firefox/firefox -contentproc -isForBrowser"
+    );
+    assert_eq!(details.environment.unwrap(), ["HOME=/home/user", "LANG=C.UTF-8"]);
+    assert!(details.cwd.unwrap_err().is_not_found());
+
+    let stat = details.stat.unwrap();
+    assert_eq!(stat.priority, 15);
+    assert_eq!(stat.nice, -5);
+    assert_eq!(ProcessStat::ticks_to_seconds(stat.starttime), 50000. / hertz());
+}
+
+#[test]
+fn every_stat_field() {
+    let stat = read_process_stat(&fixture("proc"), 42).unwrap();
+    assert_eq!(stat.pid, 42);
+    assert_eq!((stat.pgrp, stat.session, stat.tty_nr, stat.tpgid), (42, 42, 0, -1));
+    assert_eq!(stat.flags, 4194560);
+    assert_eq!((stat.minflt, stat.majflt), (1000, 10));
+    assert_eq!((stat.priority, stat.nice, stat.num_threads), (15, -5, 24));
+    assert_eq!((stat.vsize, stat.rss), (12345678, 1024));
+    assert_eq!(stat.rsslim, u64::MAX);
+    assert_eq!((stat.exit_signal, stat.processor), (17, 3));
+    assert_eq!(stat.exit_code, 0);
+
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    let process = &sampler.processes().unwrap()[&42];
+    assert_eq!((process.threads, process.nice, process.priority, process.processor), (24, -5, 15, 3));
+    assert_eq!(process.start_time, 50000. / hertz());
+}
+
+#[test]
+fn malformed_stat() {
+    assert!(ProcessStat::parse("").is_err());
+    assert!(ProcessStat::parse("12 no parentheses S 1").is_err());
+
+    // an unterminated line from an older kernel
+    let stat = ProcessStat::parse("12 (old) S 1 12 12 0 -1 0 0 0 0 0 7 3").unwrap();
+    assert_eq!((stat.pid, stat.ppid, stat.tpgid), (12, 1, -1));
+    assert_eq!(stat.total_time(), 10);
+    assert_eq!(stat.processor, 0);
+
+    // values that do not fit are read as 0 instead of shifting the others
+    let stat = ProcessStat::parse("12 (big) S 1 12 12 0 -1 0 0 0 0 0 x 3").unwrap();
+    assert_eq!((stat.utime, stat.stime), (0, 3));
+}
+
+#[test]
+fn malformed_files() {
+    let root = fixture("broken");
+    assert!(matches!(read_uptime(&root), Err(taskmanager_core::Error::Parse { .. })));
+    assert!(matches!(
+        read_cpu_usage(&root, &mut Vec::new()),
+        Err(taskmanager_core::Error::Parse { .. })
+    ));
+    assert!(read_memory_usage(&root).unwrap_err().is_not_found());
+}
+
+#[test]
+fn missing_root() {
+    let mut sampler = Sampler::with_root(fixture("does-not-exist"));
+    assert!(sampler.processes().unwrap_err().is_not_found());
+    assert!(sampler.sample().is_err());
+}
+
+#[test]
+fn missing_machine_files() {
+    // no diskstats and no net/dev, like in some containers
+    let snapshot = Sampler::with_root(fixture("minimal")).sample().unwrap();
+    assert!(snapshot.processes.is_empty() && snapshot.disks.is_empty() && snapshot.network.is_empty());
+}
+
+#[test]
+fn threads() {
+    let mut cpu_tracker = taskmanager_core::CpuTracker::new();
+    cpu_tracker.begin_pass(&fixture("proc"));
+    let threads = read_threads(&fixture("proc"), 42, &mut cpu_tracker).unwrap();
+
+    // task/45 has no stat file, like a thread that exited while the directory was read
+    let names: Vec<&str> = threads.iter().map(|thread| thread.name.as_str()).collect();
+    assert_eq!(names, ["Web Content", "DOM Worker", "Socket Thread"]);
+    assert_eq!(threads[1].tid, 43);
+    assert_eq!(threads[1].state, "R");
+    assert_eq!(threads[1].processor, 3);
+    assert_close(threads[1].cpu, lifetime_usage(290, 50000));
+}
+
+#[test]
+fn threads_are_collected_on_request() {
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    assert!(sampler.processes().unwrap()[&42].thread_list.is_empty());
+
+    sampler.set_collect_threads(true);
+    let processes = sampler.processes().unwrap();
+    assert_eq!(processes[&42].thread_list.len(), 3);
+    // a process without a task directory
+    assert!(processes[&300].thread_list.is_empty());
+
+    // the threads are measured apart from the process with the same id
+    let processes = sampler.processes().unwrap();
+    assert_eq!(processes[&42].thread_list[0].cpu, 0.);
+    assert_eq!(processes[&42].cpu, 0.);
+}
+
+#[test]
+fn process_tree() {
+    let snapshot = Sampler::with_root(fixture("proc")).sample().unwrap();
+    let tree = &snapshot.tree;
+
+    // 300 has no PPid line
+    assert_eq!(tree.roots(), [1, 300]);
+    assert_eq!(tree.children(1), [42, 100, 101, 200]);
+    // 500 was started by a thread of 42, so it is missing from the children file of the main thread
+    assert!(get_children_processes(&fixture("proc"), 42).unwrap().is_empty());
+    assert_eq!(tree.children(42), [500]);
+    assert!(tree.children(500).is_empty());
+    assert!(tree.children(999).is_empty());
+
+    assert!(ProcessTree::build(&Default::default()).roots().is_empty());
+}
+
+#[test]
+fn extra_columns() {
+    let root = fixture("proc");
+    let status = read_process_info(&root, 42).unwrap();
+    assert_eq!((status.virtual_memory, status.shared_memory, status.swap), (2_048_000, 61_440, 1024));
+    assert_eq!((status.voluntary_context_switches, status.involuntary_context_switches), (1500, 25));
+
+    let mut sampler = Sampler::with_root(root);
+    let processes = sampler.processes().unwrap();
+    let process = &processes[&42];
+    assert_eq!((process.virtual_memory, process.shared_memory, process.swap), (2000., 60., 1.));
+    assert_close(process.elapsed_time, 1000. - 50000. / hertz());
+    assert_close(process.cpu_time, 500. / hertz());
+    assert_eq!(process.command_line, "/usr/lib/firefox/firefox -contentproc -isForBrowser");
+    assert_eq!(process.cgroup, "/user.slice/user-1000.slice/app.slice/firefox.scope");
+    assert_eq!((process.tty.as_str(), process.session), ("", 42));
+    assert_eq!(process.open_files, Some(3));
+    assert_close(process.io_read_rate.unwrap(), 1_000_000. / process.elapsed_time);
+
+    // 300 has none of these files
+    let process = &processes[&300];
+    assert_eq!((process.open_files, process.io_read_rate), (None, None));
+    assert!(process.cgroup.is_empty() && process.command_line.is_empty());
+
+    // the values that are not asked for are not read
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    sampler.set_fields(ProcessFields { cgroup: true, ..ProcessFields::NONE });
+    let process = &sampler.processes().unwrap()[&42];
+    assert_eq!(process.cgroup, "/user.slice/user-1000.slice/app.slice/firefox.scope");
+    assert_eq!((process.open_files, process.io_read_rate), (None, None));
+    assert!(process.command_line.is_empty());
+    assert_eq!(process.virtual_memory, 2000.);
+}
+
+#[test]
+fn io_rates() {
+    let io = read_process_io(&fixture("proc"), 42).unwrap();
+    assert_eq!((io.rchar, io.wchar, io.syscr, io.syscw), (5_000_000, 3_000_000, 400, 300));
+    assert_eq!((io.read_bytes, io.write_bytes), (1_000_000, 500_000));
+
+    let mut io_tracker = IoTracker::new();
+    io_tracker.begin_pass();
+    assert_eq!(io_tracker.process_rates(42, 50000, 100., &io), (10_000., 5_000.));
+    io_tracker.end_pass();
+
+    // nothing was read or written since the previous pass
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    io_tracker.begin_pass();
+    assert_eq!(io_tracker.process_rates(42, 50000, 100., &io), (0., 0.));
+    // the pid was reused by a process that started later
+    assert_eq!(io_tracker.process_rates(42, 60000, 100., &io), (10_000., 5_000.));
+}
+
+#[test]
+fn terminal_names() {
+    let tty = |tty_nr: i32| {
+        let stat = ProcessStat::parse(&format!("1 (sh) S 0 1 1 {} -1", tty_nr)).unwrap();
+        stat.tty_name()
+    };
+    assert_eq!(tty(0), None);
+    assert_eq!(tty(136 << 8 | 3).unwrap(), "pts/3");
+    assert_eq!(tty(4 << 8 | 1).unwrap(), "tty1");
+    assert_eq!(tty(4 << 8 | 65).unwrap(), "ttyS1");
+    // minor numbers above 255 are stored above the major number
+    assert_eq!(tty(136 << 8 | 1 << 20 | 4).unwrap(), "pts/260");
+}
+
+#[test]
+fn disk_usage_since_boot() {
+    let root = fixture("proc");
+    let mut previous = DiskSample::default();
+    let disks = read_disk_usage(&root, &mut previous).unwrap();
+    let names: Vec<&str> = disks.iter().map(|disk| disk.name.as_str()).collect();
+    assert_eq!(names, ["sda", "sda1", "loop1", "loop10", "nvme0n1", "nvme0n1p1"]);
+    assert_eq!(previous.uptime, 1000.);
+
+    // a line of an older kernel, without the discard and flush counters
+    let stats = read_disk_stats(&root).unwrap();
+    assert_eq!((stats[4].major, stats[4].minor, stats[4].in_progress), (259, 0, 2));
+
+    let sda = &disks[0];
+    assert_close(sda.read_bytes_per_second, 200_000. * 512. / 1000.);
+    assert_close(sda.write_bytes_per_second, 400_000. * 512. / 1000.);
+    assert_close(sda.reads_per_second, 1.);
+    assert_close(sda.writes_per_second, 2.);
+    // busy for 10 of the 1000 seconds
+    assert_close(sda.utilization, 1.);
+    assert_close(sda.queue_depth, 0.013);
+
+    let partitions: Vec<bool> = disks.iter().map(|disk| disk.is_partition).collect();
+    assert_eq!(partitions, [false, true, false, false, false, true]);
+    let virtual_disks: Vec<bool> = disks.iter().map(|disk| disk.is_virtual).collect();
+    assert_eq!(virtual_disks, [false, false, true, true, false, false]);
+
+    // the uptime of the fixture does not advance, so there is nothing to measure
+    let disks = read_disk_usage(&root, &mut previous).unwrap();
+    assert!(disks.iter().all(|disk| disk.read_bytes_per_second == 0. && disk.utilization == 0.));
+
+    assert!(read_disk_usage(&fixture("broken"), &mut DiskSample::default()).is_err());
+}
+
+#[test]
+fn network_usage_since_boot() {
+    let root = fixture("proc");
+    let mut previous = NetworkSample::default();
+    let interfaces = read_network_usage(&root, &mut previous).unwrap();
+    let names: Vec<&str> = interfaces.iter().map(|interface| interface.name.as_str()).collect();
+    assert_eq!(names, ["lo", "eth0", "veth1a2b3c"]);
+
+    let eth0 = &interfaces[1];
+    assert!(!eth0.is_loopback && !eth0.is_virtual);
+    assert_close(eth0.rx_bytes_per_second, 2000.);
+    assert_close(eth0.tx_bytes_per_second, 1000.);
+    assert_close(eth0.rx_packets_per_second, 3.);
+    assert_close(eth0.tx_packets_per_second, 1.5);
+    assert_eq!((eth0.errors, eth0.dropped), (3, 5));
+    assert_close(eth0.errors_per_second, 3. / 1000.);
+    assert_close(eth0.dropped_per_second, 5. / 1000.);
+    assert!(interfaces[0].is_loopback);
+    assert!(interfaces[2].is_virtual);
+
+    let stats = read_network_stats(&root).unwrap();
+    assert_eq!((stats[1].rx_errors, stats[1].rx_dropped, stats[1].tx_errors), (2, 5, 1));
+
+    assert!(read_network_usage(&fixture("broken"), &mut NetworkSample::default()).is_err());
+}
+
+#[test]
+fn socket_tables() {
+    let sockets = read_sockets(&fixture("proc")).unwrap();
+    // there is no udp6 table, like on a machine without IPv6
+    let ends: Vec<(String, String, &str)> = sockets
+        .iter()
+        .map(|socket| (socket.local(), socket.remote(), socket.state.as_str()))
+        .collect();
+    assert_eq!(
+        ends,
+        [
+            (String::from("127.0.0.1:631"), String::from("0.0.0.0:0"), "LISTEN"),
+            (String::from("10.0.2.15:41908"), String::from("93.184.216.34:443"), "ESTABLISHED"),
+            (String::from("[::1]:22"), String::from("[::]:0"), "LISTEN"),
+            (String::from("0.0.0.0:5353"), String::from("0.0.0.0:0"), "UNCONN"),
+            (String::from("/run/systemd/private"), String::new(), "LISTEN"),
+            (String::new(), String::new(), "CONNECTED"),
+        ]
+    );
+    assert_eq!(sockets[1].protocol, SocketProtocol::Tcp);
+    assert_eq!((sockets[1].local_port, sockets[1].inode), (41908, 23456));
+    assert_eq!(sockets[3].protocol, SocketProtocol::Udp);
+
+    assert_eq!(socket_inode("socket:[12345]"), Some(12345));
+    assert_eq!(socket_inode("/dev/null"), None);
+    assert!(read_sockets(&fixture("broken")).unwrap_err().is_not_found());
+}
+
+#[test]
+fn sockets_of_processes() {
+    let root = fixture("proc");
+    let sockets = read_process_sockets(&root, 42).unwrap();
+    assert_eq!(sockets.len(), 1);
+    assert_eq!((sockets[0].0, sockets[0].1.local()), (2, String::from("127.0.0.1:631")));
+    assert_eq!(ProcessDetails::read(&root, 42).sockets.unwrap().len(), 1);
+    // 300 has no fd directory
+    assert!(read_process_sockets(&root, 300).is_err());
+
+    let connections = read_connections(&root).unwrap();
+    let owners: Vec<(u64, Vec<u32>)> = connections
+        .iter()
+        .map(|connection| (connection.socket.inode, connection.pids.clone()))
+        .collect();
+    // 500 holds the socket of 42 twice
+    assert_eq!(
+        owners,
+        [
+            (12345, vec![42, 500]),
+            (23456, vec![]),
+            (34567, vec![]),
+            (45678, vec![]),
+            (56789, vec![1]),
+            (56790, vec![]),
+        ]
+    );
+}
+
+#[test]
+fn handles() {
+    let root = fixture("proc");
+    let handles = find_handles(&root, "/dev/").unwrap();
+    let found: Vec<(u32, String)> = handles.iter().map(|handle| (handle.pid, handle.path.clone())).collect();
+    assert_eq!(
+        found,
+        [
+            (1, String::from("/dev/null")),
+            (42, String::from("/dev/null")),
+            (42, String::from("/dev/pts/3")),
+        ]
+    );
+    // 1 has no fdinfo directory
+    assert!(matches!(handles[0].kind, HandleKind::FileDescriptor(0, None)));
+    assert!(matches!(handles[1].kind, HandleKind::FileDescriptor(0, Some(AccessMode::Read))));
+    assert!(matches!(handles[2].kind, HandleKind::FileDescriptor(1, Some(AccessMode::ReadWrite))));
+
+    // the two mappings of the library are one handle
+    let mapped: Vec<(String, String)> = find_handles(&root, "lib")
+        .unwrap()
+        .into_iter()
+        .filter_map(|handle| match handle.kind {
+            HandleKind::MemoryMap(permissions) => Some((handle.path, permissions)),
+            HandleKind::FileDescriptor(..) => None,
+        })
+        .collect();
+    assert_eq!(
+        mapped,
+        [
+            (String::from("/usr/lib/firefox/firefox"), String::from("r-x")),
+            (String::from("/usr/lib/firefox/libxul.so"), String::from("r-x")),
+        ]
+    );
+    let documents = find_handles(&root, "My Documents").unwrap();
+    assert_eq!(documents.len(), 1);
+    assert!(matches!(&documents[0].kind, HandleKind::MemoryMap(permissions) if permissions == "rw-"));
+    assert!(find_handles(&root, "heap").unwrap().is_empty());
+
+    assert_eq!(AccessMode::parse_fdinfo("pos:\t0\nflags:\t02100001\n"), Some(AccessMode::Write));
+    assert!(find_handles(&fixture("does-not-exist"), "/").unwrap_err().is_not_found());
+}
+
+#[test]
+fn priorities() {
+    let root = fixture("proc");
+    assert_eq!(read_process_info(&root, 42).unwrap().cpus_allowed, [0, 1, 2, 3, 6]);
+    let mut sampler = Sampler::with_root(root.clone());
+    let processes = sampler.processes().unwrap();
+    assert_eq!(processes[&42].cpu_affinity, [0, 1, 2, 3, 6]);
+    // the pids of a fixture are not asked about with system calls
+    assert_eq!(processes[&42].io_priority, None);
+    assert!(processes[&1].cpu_affinity.is_empty());
+
+    assert_eq!(parse_cpu_list("0-2,5,7-8\n"), [0, 1, 2, 5, 7, 8]);
+    assert_eq!(parse_cpu_list("3,x,1-"), [3]);
+    assert_eq!(parse_cpu_list("8190-4000000000,9000").len(), 2);
+    assert_eq!(format_cpu_list(&[0, 1, 2, 5, 7, 8]), "0-2,5,7-8");
+    assert_eq!(format_cpu_list(&[]), "");
+
+    let best_effort = IoPriority::from_raw((2 << 13) | 4);
+    assert_eq!((best_effort.class, best_effort.level), (IoPriorityClass::BestEffort, 4));
+    assert_eq!(best_effort.label(), "best-effort 4");
+    let idle = IoPriority { class: IoPriorityClass::Idle, level: 6 };
+    assert_eq!((idle.as_raw(), idle.label()), (3 << 13, String::from("idle")));
+
+    assert!(read_io_priority(std::process::id()).is_ok());
+    assert!(set_nice(&ProcRoot::default(), 0, 10).unwrap_err().is_not_found());
+    // 42 of the fixture is some other process of this machine
+    let error = set_nice(&root, 42, 10).unwrap_err();
+    assert_eq!((error.raw_os_error(), error.is_not_found()), (None, false));
+    assert!(error.to_string().contains("not the local /proc"));
+    let error = set_cpu_affinity(&root, 42, &[0, 1024]).unwrap_err();
+    assert_eq!(error.raw_os_error(), Some(libc::EINVAL));
+}
+
+#[test]
+fn user_names_of_other_roots() {
+    // the local /etc/passwd says nothing about the users of another root
+    let mut sampler = Sampler::with_root(fixture("proc"));
+    assert_eq!(sampler.processes().unwrap()[&42].user, "1000");
+
+    let root = fixture("proc").with_passwd(format!("{}/tests/fixtures/etc/passwd", env!("CARGO_MANIFEST_DIR")));
+    let mut sampler = Sampler::with_root(root);
+    assert_eq!(sampler.processes().unwrap()[&42].user, "user");
+
+    assert!(ProcRoot::default().is_local());
+    assert!(ProcRoot::new("/proc/").is_local());
+    assert!(ProcRoot::new("/proc/.").is_local());
+    assert!(!fixture("proc").is_local());
+    assert_eq!(fixture("proc").passwd(), None);
+}